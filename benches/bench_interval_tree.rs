@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::prelude::ThreadRng;
+use rand::{thread_rng, Rng};
+use space_partitioning::interval_tree::Interval;
+use space_partitioning::IntervalTree;
+use std::iter::FromIterator;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    c.bench_function("overlap_search_all tree(n=16384)", |b| {
+        let mut rng = thread_rng();
+        let tree = build_random_tree(&mut rng, 16384);
+        b.iter(|| {
+            let interval = random_interval(&mut rng, 0..65536, 1..256);
+            tree.overlap_search_all(interval)
+        })
+    });
+
+    c.bench_function("overlap_search_all static(n=16384)", |b| {
+        let mut rng = thread_rng();
+        let index = build_random_tree(&mut rng, 16384).into_static();
+        b.iter(|| {
+            let interval = random_interval(&mut rng, 0..65536, 1..256);
+            index.overlap_search_all(interval)
+        })
+    });
+
+    c.bench_function("stab static(n=16384)", |b| {
+        let mut rng = thread_rng();
+        let index = build_random_tree(&mut rng, 16384).into_static();
+        b.iter(|| index.stab(rng.gen_range(0..65536)))
+    });
+}
+
+fn build_random_tree(rng: &mut ThreadRng, num_intervals: u32) -> IntervalTree<i32, u32> {
+    IntervalTree::from_iter(
+        (0..num_intervals).map(|id| (random_interval(rng, 0..65536, 1..256), id)),
+    )
+}
+
+#[inline]
+fn random_interval(
+    rng: &mut ThreadRng,
+    start: std::ops::Range<i32>,
+    length: std::ops::Range<i32>,
+) -> Interval<i32> {
+    let start = rng.gen_range(start);
+    Interval::new(start, start + rng.gen_range(length))
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
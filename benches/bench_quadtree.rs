@@ -1,5 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::prelude::ThreadRng;
+use rand::seq::SliceRandom;
 use rand::{thread_rng, Rng};
 use space_partitioning::quadtree::{QuadRect, QuadTreeElement, AABB};
 use space_partitioning::QuadTree;
@@ -31,6 +32,26 @@ fn criterion_benchmark(c: &mut Criterion) {
         })
     });
 
+    c.bench_function(
+        "insert grid lazily split tree(w=256, h=256, depth=4)",
+        |b| {
+            b.iter(|| {
+                let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 4, 4, 1);
+                insert_grid(&mut tree);
+                tree
+            })
+        },
+    );
+
+    c.bench_function("insert grid pre-split tree(w=256, h=256, depth=4)", |b| {
+        b.iter(|| {
+            let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 4, 4, 1);
+            tree.pre_split_to_depth(4);
+            insert_grid(&mut tree);
+            tree
+        })
+    });
+
     c.bench_function("visit_leaves tree(w=256, h=256, depth=8)", |b| {
         let mut rng = thread_rng();
         let tree = build_random_tree(&mut rng, 1024, 256, 256, 4);
@@ -50,6 +71,128 @@ fn criterion_benchmark(c: &mut Criterion) {
         })
     });
 
+    {
+        let mut rng = thread_rng();
+        let elements: Vec<_> = (0..4096)
+            .map(|id| QuadTreeElement::new(id, random_aabb(&mut rng, 1..256, 1..256, 1..16, 1..16)))
+            .collect();
+
+        c.bench_function(
+            "intersect_aabb random order tree(n=4096, w=256, h=256, depth=8)",
+            |b| {
+                let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 8, 16, 1);
+                for element in &elements {
+                    tree.insert(*element).expect("insert should work");
+                }
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..64, 1..64);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+
+        c.bench_function(
+            "intersect_aabb morton order tree(n=4096, w=256, h=256, depth=8)",
+            |b| {
+                let tree = QuadTree::from_elements_sorted(
+                    QuadRect::new(0, 0, 256, 256),
+                    8,
+                    16,
+                    1,
+                    &elements,
+                )
+                .expect("insert should work");
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..64, 1..64);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+    }
+
+    {
+        let mut rng = thread_rng();
+        let mut tree = build_churned_tree(&mut rng, 16384, 1024, 1024, 8);
+
+        c.bench_function(
+            "intersect_aabb unsorted leaves tree(n=16384, w=1024, h=1024, depth=8)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..1024, 1..1024, 1..128, 1..128);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+
+        tree.sort_leaf_elements();
+        c.bench_function(
+            "intersect_aabb sorted leaves tree(n=16384, w=1024, h=1024, depth=8)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..1024, 1..1024, 1..128, 1..128);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+    }
+
+    {
+        let mut rng = thread_rng();
+        let tree = build_random_tree(&mut rng, 1024, 256, 256, 8);
+
+        c.bench_function(
+            "intersect_aabb local tree(n=1024, w=256, h=256, depth=8)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..4, 1..4);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+
+        c.bench_function(
+            "intersect_aabb_small local tree(n=1024, w=256, h=256, depth=8)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..4, 1..4);
+                    tree.intersect_aabb_small(&aabb)
+                })
+            },
+        );
+    }
+
+    {
+        let mut rng = thread_rng();
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 4, 1024, 1);
+        tree.pre_split_to_depth(4);
+        for id in 0..4096 {
+            let aabb = random_aabb(&mut rng, 16..240, 16..240, 1..16, 1..16);
+            tree.insert(QuadTreeElement::new(id, aabb))
+                .expect("insert should work");
+        }
+        let grid = tree.as_uniform_grid().expect("the tree should be uniform");
+
+        c.bench_function(
+            "intersect_aabb pre-split tree(n=4096, w=256, h=256, depth=4)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..32, 1..32);
+                    tree.intersect_aabb(&aabb)
+                })
+            },
+        );
+
+        c.bench_function(
+            "intersect_aabb uniform grid(n=4096, w=256, h=256, depth=4)",
+            |b| {
+                b.iter(|| {
+                    let aabb = random_aabb(&mut rng, 1..256, 1..256, 1..32, 1..32);
+                    grid.intersect_aabb(&aabb)
+                })
+            },
+        );
+    }
+
     c.bench_function(
         "intersect_generic tree(n=1024, w=256, h=256, depth=4)",
         |b| {
@@ -75,6 +218,16 @@ fn criterion_benchmark(c: &mut Criterion) {
     );
 }
 
+/// Inserts a uniform grid of 32x32 small elements.
+fn insert_grid(tree: &mut QuadTree) {
+    for id in 0..1024u32 {
+        let x = (id % 32) as i32 * 8 + 2;
+        let y = (id / 32) as i32 * 8 + 2;
+        tree.insert(QuadTreeElement::new(id, AABB::new(x, y, x + 4, y + 4)))
+            .expect("insert should work");
+    }
+}
+
 fn build_random_tree(
     mut rng: &mut ThreadRng,
     num_elements: u32,
@@ -91,6 +244,37 @@ fn build_random_tree(
     tree
 }
 
+/// Builds a tree whose element slots were reused in random order by removing
+/// and inserting again half of the elements.
+fn build_churned_tree(
+    mut rng: &mut ThreadRng,
+    num_elements: u32,
+    width: i32,
+    height: i32,
+    depth: u8,
+) -> QuadTree {
+    let mut tree = QuadTree::new(QuadRect::new(0, 0, width, height), depth, 16, 1);
+    let mut elements: Vec<_> = (0..num_elements)
+        .map(|id| {
+            let aabb = random_aabb(&mut rng, 1..width, 1..height, 1..32, 1..32);
+            QuadTreeElement::new(id, aabb)
+        })
+        .collect();
+    for element in &elements {
+        tree.insert(*element).expect("insert should work");
+    }
+
+    elements.shuffle(&mut rng);
+    let (removed, _) = elements.split_at(elements.len() / 2);
+    for element in removed {
+        tree.remove(element);
+    }
+    for element in removed.iter().rev() {
+        tree.insert(*element).expect("insert should work");
+    }
+    tree
+}
+
 #[inline]
 fn random_aabb(
     rng: &mut ThreadRng,
@@ -71,6 +71,7 @@ fn main() {
     let (mut tree, mut items) = build_test_data();
     let _ = tree.insert(mouse.build_qte(&window_size));
     let mut tree_structure = collect_tree_structure(&tree);
+    let mut tree_structure_generation = tree.generation();
 
     let pb = ProgressBar::new_spinner();
     pb.set_draw_rate(5);
@@ -91,10 +92,11 @@ fn main() {
 
                         // Generate new data points
                         let (new_tree, new_items) = build_test_data();
-                        let _ = tree.insert(mouse.build_qte(&window_size));
-                        tree_structure = collect_tree_structure(&tree);
                         tree = new_tree;
                         items = new_items;
+                        let _ = tree.insert(mouse.build_qte(&window_size));
+                        tree_structure = collect_tree_structure(&tree);
+                        tree_structure_generation = tree.generation();
                     }
                     _ => {}
                 }
@@ -133,8 +135,11 @@ fn main() {
             // Compact the tree.
             tree.cleanup();
 
-            // Update the visualization.
-            tree_structure = collect_tree_structure(&tree);
+            // Update the visualization if the tree changed.
+            if tree.generation() != tree_structure_generation {
+                tree_structure = collect_tree_structure(&tree);
+                tree_structure_generation = tree.generation();
+            }
 
             // Get new intersections.
             items_under_mouse =
@@ -192,12 +197,7 @@ fn intersect_with_mouse(
     let x = pos[0] - window_size[0] * 0.5 - cursor_size * 0.5;
     let y = pos[1] - window_size[1] * 0.5 - cursor_size * 0.5;
 
-    let aabb = AABB::new(
-        x.floor() as _,
-        y.floor() as _,
-        (x + cursor_size).ceil() as _,
-        (y + cursor_size).ceil() as _,
-    );
+    let aabb = AABB::from_f64_conservative([x, y], [x + cursor_size, y + cursor_size]);
 
     HashSet::from_iter(tree.intersect_aabb(&aabb).into_iter())
 }
@@ -338,11 +338,9 @@ fn build_test_data() -> (QuadTree, Vec<Disk>) {
 
 impl Disk {
     fn get_aabb(&self) -> AABB {
-        AABB::new(
-            (self.cx - self.radius).floor() as _,
-            (self.cy - self.radius).floor() as _,
-            (self.cx + self.radius).ceil() as _,
-            (self.cy + self.radius).ceil() as _,
+        AABB::from_f64_conservative(
+            [self.cx - self.radius, self.cy - self.radius],
+            [self.cx + self.radius, self.cy + self.radius],
         )
     }
 }
@@ -351,11 +349,15 @@ impl Mouse {
     fn build_qte(&self, window_size: &[f64; 2]) -> QuadTreeElement {
         QuadTreeElement::new(
             1337,
-            AABB::new(
-                (self.pos[0] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5).floor() as _,
-                (self.pos[1] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5).floor() as _,
-                (self.pos[0] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5).ceil() as _,
-                (self.pos[1] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5).ceil() as _,
+            AABB::from_f64_conservative(
+                [
+                    self.pos[0] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5,
+                    self.pos[1] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5,
+                ],
+                [
+                    self.pos[0] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5,
+                    self.pos[1] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5,
+                ],
             ),
         )
     }
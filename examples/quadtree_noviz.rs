@@ -97,12 +97,7 @@ fn intersect_with_mouse(
     let x = pos[0] - window_size[0] * 0.5 - cursor_size * 0.5;
     let y = pos[1] - window_size[1] * 0.5 - cursor_size * 0.5;
 
-    let aabb = AABB::new(
-        x.floor() as _,
-        y.floor() as _,
-        (x + cursor_size).ceil() as _,
-        (y + cursor_size).ceil() as _,
-    );
+    let aabb = AABB::from_f64_conservative([x, y], [x + cursor_size, y + cursor_size]);
 
     let vec = tree.intersect_aabb(&aabb);
     let vec_len = vec.len();
@@ -163,11 +158,9 @@ fn build_test_data() -> (QuadTree, Vec<Disk>) {
 
 impl Disk {
     fn get_aabb(&self) -> AABB {
-        AABB::new(
-            (self.cx - self.radius).floor() as _,
-            (self.cy - self.radius).floor() as _,
-            (self.cx + self.radius).ceil() as _,
-            (self.cy + self.radius).ceil() as _,
+        AABB::from_f64_conservative(
+            [self.cx - self.radius, self.cy - self.radius],
+            [self.cx + self.radius, self.cy + self.radius],
         )
     }
 }
@@ -176,11 +169,15 @@ impl Mouse {
     fn build_qte(&self, window_size: &[f64; 2]) -> QuadTreeElement {
         QuadTreeElement::new(
             1337,
-            AABB::new(
-                (self.pos[0] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5).floor() as _,
-                (self.pos[1] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5).floor() as _,
-                (self.pos[0] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5).ceil() as _,
-                (self.pos[1] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5).ceil() as _,
+            AABB::from_f64_conservative(
+                [
+                    self.pos[0] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5,
+                    self.pos[1] - window_size[0] * 0.5 - CURSOR_SIZE * 0.5,
+                ],
+                [
+                    self.pos[0] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5,
+                    self.pos[1] - window_size[0] * 0.5 + CURSOR_SIZE * 0.5,
+                ],
             ),
         )
     }
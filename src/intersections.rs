@@ -1,3 +1,15 @@
+mod capsule;
+mod circle;
+mod distance;
+mod distance_to;
 mod intersects_with;
+mod oriented_box;
+mod ray;
 
+pub use capsule::Capsule;
+pub use circle::Circle;
+pub use distance::aabb_squared_distance;
+pub use distance_to::DistanceTo;
 pub use intersects_with::IntersectsWith;
+pub use oriented_box::OrientedBox;
+pub use ray::Ray;
@@ -0,0 +1,188 @@
+use crate::intersections::IntersectsWith;
+use crate::quadtree::{Point, AABB};
+
+/// A circle swept along a line segment, e.g. the area covered by a moving
+/// disk between two frames.
+///
+/// # Remarks
+/// Querying with a capsule rather than the disk's bounding boxes at both
+/// endpoints finds elements the disk passed through during the movement.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Capsule {
+    /// The start of the segment.
+    pub a: Point,
+    /// The end of the segment.
+    pub b: Point,
+    /// The radius of the swept circle.
+    pub radius: i32,
+}
+
+impl Capsule {
+    /// Constructs a new [`Capsule`].
+    ///
+    /// # Arguments
+    /// * [`a`] - The start of the segment.
+    /// * [`b`] - The end of the segment.
+    /// * [`radius`] - The radius of the swept circle.
+    #[inline]
+    pub fn new(a: Point, b: Point, radius: i32) -> Self {
+        Self { a, b, radius }
+    }
+
+    /// Calculates the squared distance of the capsule's segment to a box,
+    /// or zero if the segment intersects the box.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to calculate the distance to.
+    pub fn squared_segment_distance_to(&self, other: &AABB) -> f64 {
+        if self.segment_intersects(other) {
+            return 0.0;
+        }
+
+        // If the segment misses the box, the closest points are
+        // an endpoint of the segment or a corner of the box.
+        let corners = [
+            other.tl,
+            Point::new(other.br.x, other.tl.y),
+            Point::new(other.tl.x, other.br.y),
+            other.br,
+        ];
+        let to_corners = corners
+            .iter()
+            .map(|corner| self.squared_segment_distance_to_point(corner));
+        let to_endpoints = [self.a, self.b]
+            .into_iter()
+            .map(|endpoint| other.squared_distance_to_point(&endpoint) as f64);
+        to_corners.chain(to_endpoints).fold(f64::INFINITY, f64::min)
+    }
+
+    /// Calculates the squared distance of a point to the capsule's segment.
+    fn squared_segment_distance_to_point(&self, point: &Point) -> f64 {
+        let (ax, ay) = (self.a.x as f64, self.a.y as f64);
+        let (dx, dy) = (self.b.x as f64 - ax, self.b.y as f64 - ay);
+        let (px, py) = (point.x as f64 - ax, point.y as f64 - ay);
+
+        let length_squared = dx * dx + dy * dy;
+        let t = if length_squared > 0.0 {
+            ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let (ex, ey) = (px - t * dx, py - t * dy);
+        ex * ex + ey * ey
+    }
+
+    /// Tests whether the capsule's segment intersects a box by clipping
+    /// it against the box's slabs.
+    fn segment_intersects(&self, other: &AABB) -> bool {
+        let mut t_enter = 0.0f64;
+        let mut t_exit = 1.0f64;
+
+        let axes = [
+            (self.a.x, self.b.x, other.tl.x, other.br.x),
+            (self.a.y, self.b.y, other.tl.y, other.br.y),
+        ];
+        for (start, end, min, max) in axes {
+            let (start, delta) = (start as f64, end as f64 - start as f64);
+            let (min, max) = (min as f64, max as f64);
+
+            if delta == 0.0 {
+                // The segment is parallel to the slab.
+                if start < min || start > max {
+                    return false;
+                }
+                continue;
+            }
+
+            let t_min = (min - start) / delta;
+            let t_max = (max - start) / delta;
+            t_enter = t_enter.max(t_min.min(t_max));
+            t_exit = t_exit.min(t_min.max(t_max));
+            if t_enter > t_exit {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl IntersectsWith<AABB> for Capsule {
+    /// Tests whether this [`Capsule`] intersects with an [`AABB`].
+    /// Shapes that only touch are considered intersecting.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    fn intersects_with(&self, other: &AABB) -> bool {
+        let radius = self.radius as f64;
+        self.squared_segment_distance_to(other) <= radius * radius
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn segment_distance_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+
+        // Crossing the box.
+        let capsule = Capsule::new(Point::new(-5, 5), Point::new(15, 5), 0);
+        assert_eq!(capsule.squared_segment_distance_to(&aabb), 0.0);
+
+        // Passing the box at a distance of 2.
+        let capsule = Capsule::new(Point::new(12, -5), Point::new(12, 25), 0);
+        assert_eq!(capsule.squared_segment_distance_to(&aabb), 4.0);
+
+        // Diagonally passing the bottom-right corner at a distance of sqrt(18).
+        let capsule = Capsule::new(Point::new(20, 6), Point::new(6, 20), 0);
+        assert!((capsule.squared_segment_distance_to(&aabb) - 18.0).abs() < 1e-9);
+
+        // Ending before the box.
+        let capsule = Capsule::new(Point::new(-10, 5), Point::new(-3, 5), 0);
+        assert_eq!(capsule.squared_segment_distance_to(&aabb), 9.0);
+
+        // A degenerate segment is a point.
+        let capsule = Capsule::new(Point::new(13, 14), Point::new(13, 14), 0);
+        assert_eq!(capsule.squared_segment_distance_to(&aabb), 25.0);
+    }
+
+    #[test]
+    fn grazing_a_side_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        let capsule = Capsule::new(Point::new(12, -5), Point::new(12, 25), 2);
+        assert!(capsule.intersects_with(&aabb));
+
+        let capsule = Capsule::new(Point::new(12, -5), Point::new(12, 25), 1);
+        assert!(!capsule.intersects_with(&aabb));
+    }
+
+    #[test]
+    fn grazing_a_corner_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+
+        // The bounding box of the capsule overlaps the box for both radii.
+        let capsule = Capsule::new(Point::new(20, 6), Point::new(6, 20), 5);
+        assert!(capsule.intersects_with(&aabb));
+
+        let capsule = Capsule::new(Point::new(20, 6), Point::new(6, 20), 4);
+        assert!(!capsule.intersects_with(&aabb));
+    }
+
+    #[test]
+    fn endpoints_in_separate_boxes_works() {
+        let start = AABB::new(0, 0, 10, 10);
+        let between = AABB::new(20, 0, 30, 10);
+        let end = AABB::new(40, 0, 50, 10);
+        let elsewhere = AABB::new(20, 20, 30, 30);
+
+        // The endpoints are in the outer boxes; the box in between was tunneled.
+        let capsule = Capsule::new(Point::new(5, 5), Point::new(45, 5), 1);
+        assert!(capsule.intersects_with(&start));
+        assert!(capsule.intersects_with(&between));
+        assert!(capsule.intersects_with(&end));
+        assert!(!capsule.intersects_with(&elsewhere));
+    }
+}
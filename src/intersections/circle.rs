@@ -0,0 +1,99 @@
+use crate::intersections::{DistanceTo, IntersectsWith};
+use crate::quadtree::{Point, AABB};
+
+/// A circle, e.g. the area within a fixed radius around a point of interest.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Circle {
+    /// The center of the circle.
+    pub center: Point,
+    /// The radius of the circle.
+    pub radius: i32,
+}
+
+impl Circle {
+    /// Constructs a new [`Circle`].
+    ///
+    /// # Arguments
+    /// * [`center`] - The center of the circle.
+    /// * [`radius`] - The radius of the circle.
+    #[inline]
+    pub fn new(center: Point, radius: i32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Returns the squared radius of the circle.
+    #[inline]
+    fn squared_radius(&self) -> u64 {
+        let radius = self.radius.max(0) as u64;
+        radius * radius
+    }
+}
+
+impl IntersectsWith<AABB> for Circle {
+    /// Tests whether this [`Circle`] intersects with an [`AABB`].
+    /// Shapes that only touch are considered intersecting.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    #[inline]
+    fn intersects_with(&self, other: &AABB) -> bool {
+        other.squared_distance_to_point(&self.center) <= self.squared_radius()
+    }
+}
+
+impl DistanceTo<AABB> for Circle {
+    /// Calculates the squared distance between the outline of this [`Circle`] and
+    /// an [`AABB`], rounded down, or zero if the circle intersects the box.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to calculate the distance to.
+    fn distance_sq_to(&self, other: &AABB) -> u64 {
+        let to_center = other.squared_distance_to_point(&self.center);
+        if to_center <= self.squared_radius() {
+            return 0;
+        }
+
+        let distance = (to_center as f64).sqrt() - self.radius.max(0) as f64;
+        (distance * distance).floor() as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intersects_with_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert!(Circle::new(Point::new(5, 5), 1).intersects_with(&aabb));
+        assert!(Circle::new(Point::new(13, 5), 3).intersects_with(&aabb));
+        assert!(!Circle::new(Point::new(13, 5), 2).intersects_with(&aabb));
+
+        // Close to the corner, but outside of the radius.
+        assert!(!Circle::new(Point::new(13, 14), 4).intersects_with(&aabb));
+        assert!(Circle::new(Point::new(13, 14), 5).intersects_with(&aabb));
+    }
+
+    #[test]
+    fn distance_to_inside_is_zero() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert_eq!(Circle::new(Point::new(5, 5), 1).distance_sq_to(&aabb), 0);
+        assert_eq!(
+            Circle::new(Point::new(-20, 5), 100).distance_sq_to(&aabb),
+            0
+        );
+        assert_eq!(Circle::new(Point::new(13, 5), 3).distance_sq_to(&aabb), 0);
+    }
+
+    #[test]
+    fn distance_to_outside_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert_eq!(Circle::new(Point::new(15, 5), 2).distance_sq_to(&aabb), 9);
+        assert_eq!(Circle::new(Point::new(13, 14), 2).distance_sq_to(&aabb), 9);
+        assert_eq!(Circle::new(Point::new(13, 14), 0).distance_sq_to(&aabb), 25);
+        assert_eq!(
+            (&Circle::new(Point::new(-5, 5), 3)).distance_sq_to(&aabb),
+            4
+        );
+    }
+}
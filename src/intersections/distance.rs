@@ -0,0 +1,62 @@
+use crate::quadtree::AABB;
+
+/// Calculates the squared distance between the closest points of two boxes,
+/// e.g. to prioritize candidate pairs in a broad phase.
+///
+/// # Remarks
+/// Intersecting or touching boxes have a distance of zero. The calculation is
+/// carried out in 64-bit integers and therefore does not overflow; distances
+/// exceeding the range of `u64` saturate.
+///
+/// # Arguments
+/// * [`a`] - The first box.
+/// * [`b`] - The second box.
+///
+/// # Example
+/// ```rust
+/// use space_partitioning::intersections::aabb_squared_distance;
+/// use space_partitioning::quadtree::AABB;
+///
+/// let a = AABB::new(0, 0, 10, 10);
+/// let b = AABB::new(13, 14, 20, 20);
+/// assert_eq!(aabb_squared_distance(&a, &b), 3 * 3 + 4 * 4);
+/// ```
+#[inline]
+pub fn aabb_squared_distance(a: &AABB, b: &AABB) -> u64 {
+    a.squared_distance_to(b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlapping_boxes_have_zero_distance() {
+        let a = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(5, 5, 20, 20)), 0);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(2, 2, 4, 4)), 0);
+        assert_eq!(aabb_squared_distance(&a, &a), 0);
+    }
+
+    #[test]
+    fn edge_adjacent_boxes_have_zero_distance() {
+        let a = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(10, 0, 20, 10)), 0);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(3, -5, 7, 0)), 0);
+    }
+
+    #[test]
+    fn separated_boxes_work() {
+        let a = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(13, 0, 20, 10)), 9);
+        assert_eq!(aabb_squared_distance(&a, &AABB::new(13, 14, 20, 20)), 25);
+        assert_eq!(aabb_squared_distance(&AABB::new(-20, -20, -3, -4), &a), 25);
+    }
+
+    #[test]
+    fn distant_boxes_do_not_overflow() {
+        let a = AABB::new(i32::MIN, 0, i32::MIN, 0);
+        let b = AABB::new(i32::MAX, 0, i32::MAX, 0);
+        assert_eq!(aabb_squared_distance(&a, &b), (u32::MAX as u64).pow(2));
+    }
+}
@@ -0,0 +1,20 @@
+pub trait DistanceTo<T = Self> {
+    /// Calculates the squared distance between the closest points of this element
+    /// and the [`other`].
+    ///
+    /// # Returns
+    /// - `0` if the two elements intersect or touch.
+    fn distance_sq_to(&self, other: &T) -> u64;
+}
+
+impl<S, T> DistanceTo<T> for &S
+where
+    S: DistanceTo<T> + ?Sized,
+{
+    /// Calculates the squared distance of the referenced element to the [`other`],
+    /// such that shapes can be passed both by value and by reference.
+    #[inline]
+    fn distance_sq_to(&self, other: &T) -> u64 {
+        (**self).distance_sq_to(other)
+    }
+}
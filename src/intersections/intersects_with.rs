@@ -5,3 +5,15 @@ pub trait IntersectsWith<T = Self> {
     /// - `true` if the two elements intersect.
     fn intersects_with(&self, other: &T) -> bool;
 }
+
+impl<S, T> IntersectsWith<T> for &S
+where
+    S: IntersectsWith<T> + ?Sized,
+{
+    /// Tests whether the referenced element intersects with the [`other`],
+    /// such that shapes can be passed both by value and by reference.
+    #[inline]
+    fn intersects_with(&self, other: &T) -> bool {
+        (**self).intersects_with(other)
+    }
+}
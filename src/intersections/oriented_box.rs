@@ -0,0 +1,119 @@
+use crate::intersections::IntersectsWith;
+use crate::quadtree::AABB;
+
+/// A rectangle rotated about its center, i.e. an oriented bounding box.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct OrientedBox {
+    /// The center X coordinate.
+    pub cx: f32,
+    /// The center Y coordinate.
+    pub cy: f32,
+    /// The half-width and half-height of the box before rotation.
+    pub half_extents: [f32; 2],
+    /// The counter-clockwise rotation of the box in radians.
+    pub angle: f32,
+}
+
+impl OrientedBox {
+    /// Constructs a new [`OrientedBox`].
+    ///
+    /// # Arguments
+    /// * [`cx`] - The center X coordinate.
+    /// * [`cy`] - The center Y coordinate.
+    /// * [`half_extents`] - The half-width and half-height of the box before rotation.
+    /// * [`angle`] - The counter-clockwise rotation of the box in radians.
+    #[inline]
+    pub fn new(cx: f32, cy: f32, half_extents: [f32; 2], angle: f32) -> Self {
+        Self {
+            cx,
+            cy,
+            half_extents,
+            angle,
+        }
+    }
+
+    /// Gets the local axes of the box, i.e. the rotated X and Y axes.
+    #[inline]
+    fn axes(&self) -> [[f32; 2]; 2] {
+        let (sin, cos) = self.angle.sin_cos();
+        [[cos, sin], [-sin, cos]]
+    }
+}
+
+impl IntersectsWith<AABB> for OrientedBox {
+    /// Tests whether this [`OrientedBox`] intersects with an [`AABB`] using the
+    /// separating axis theorem. Boxes that only touch are considered intersecting.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    fn intersects_with(&self, other: &AABB) -> bool {
+        let box_cx = (other.tl.x as f32 + other.br.x as f32) * 0.5;
+        let box_cy = (other.tl.y as f32 + other.br.y as f32) * 0.5;
+        let box_hx = (other.br.x as f32 - other.tl.x as f32) * 0.5;
+        let box_hy = (other.br.y as f32 - other.tl.y as f32) * 0.5;
+
+        let [u, v] = self.axes();
+        let [hu, hv] = self.half_extents;
+        let dx = self.cx - box_cx;
+        let dy = self.cy - box_cy;
+
+        // The axes of the AABB and the oriented box are the only candidates
+        // for a separating axis.
+        for axis in [[1.0, 0.0], [0.0, 1.0], u, v] {
+            let distance = (dx * axis[0] + dy * axis[1]).abs();
+            let self_radius = hu * (u[0] * axis[0] + u[1] * axis[1]).abs()
+                + hv * (v[0] * axis[0] + v[1] * axis[1]).abs();
+            let other_radius = box_hx * axis[0].abs() + box_hy * axis[1].abs();
+            if distance > self_radius + other_radius {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn overlapping_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        let obb = OrientedBox::new(11.0, 5.0, [3.0, 1.0], 0.5);
+        assert!(obb.intersects_with(&aabb));
+    }
+
+    #[test]
+    fn contained_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        let obb = OrientedBox::new(5.0, 5.0, [1.0, 2.0], 0.3);
+        assert!(obb.intersects_with(&aabb));
+
+        // The AABB is contained in the oriented box.
+        let obb = OrientedBox::new(5.0, 5.0, [20.0, 20.0], FRAC_PI_4);
+        assert!(obb.intersects_with(&aabb));
+    }
+
+    #[test]
+    fn rotated_past_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+
+        // An axis-aligned square overlapping the corner of the box.
+        let obb = OrientedBox::new(11.5, 11.5, [2.0, 2.0], 0.0);
+        assert!(obb.intersects_with(&aabb));
+
+        // Rotated by 45 degrees, the square's edge just passes the corner,
+        // even though the square's own bounding box still overlaps.
+        let obb = OrientedBox::new(11.5, 11.5, [2.0, 2.0], FRAC_PI_4);
+        assert!(!obb.intersects_with(&aabb));
+    }
+
+    #[test]
+    fn separated_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        let obb = OrientedBox::new(20.0, 5.0, [3.0, 1.0], 1.0);
+        assert!(!obb.intersects_with(&aabb));
+    }
+}
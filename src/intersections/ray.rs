@@ -0,0 +1,117 @@
+use crate::intersections::IntersectsWith;
+use crate::quadtree::AABB;
+
+/// A half-infinite ray starting at an origin and extending in a direction.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    /// The origin X coordinate.
+    pub x: f32,
+    /// The origin Y coordinate.
+    pub y: f32,
+    /// The X component of the direction.
+    pub dx: f32,
+    /// The Y component of the direction.
+    pub dy: f32,
+    /// The reciprocal of the X component of the direction.
+    inv_dx: f32,
+    /// The reciprocal of the Y component of the direction.
+    inv_dy: f32,
+}
+
+impl Ray {
+    /// Constructs a new [`Ray`].
+    ///
+    /// # Remarks
+    /// The direction does not need to be normalized. Distances along the ray,
+    /// as returned by [`Ray::entry_t()`], are given in multiples of its length.
+    ///
+    /// # Arguments
+    /// * [`x`] - The origin X coordinate.
+    /// * [`y`] - The origin Y coordinate.
+    /// * [`dx`] - The X component of the direction.
+    /// * [`dy`] - The Y component of the direction.
+    #[inline]
+    pub fn new(x: f32, y: f32, dx: f32, dy: f32) -> Self {
+        Self {
+            x,
+            y,
+            dx,
+            dy,
+            inv_dx: 1.0 / dx,
+            inv_dy: 1.0 / dy,
+        }
+    }
+
+    /// Determines where the ray enters the specified box, i.e. the smallest `t`
+    /// such that the point `(x + t * dx, y + t * dy)` lies in the box.
+    ///
+    /// # Returns
+    /// The entry `t`, which is zero if the origin lies in the box, or
+    /// `None` if the ray misses the box or the box is behind the origin.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    pub fn entry_t(&self, other: &AABB) -> Option<f32> {
+        // https://gamedev.stackexchange.com/a/18459/10433
+        let t1 = (other.tl.x as f32 - self.x) * self.inv_dx;
+        let t2 = (other.br.x as f32 - self.x) * self.inv_dx;
+        let t3 = (other.br.y as f32 - self.y) * self.inv_dy;
+        let t4 = (other.tl.y as f32 - self.y) * self.inv_dy;
+
+        let tmin = t1.min(t2).max(t3.min(t4));
+        let tmax = t1.max(t2).min(t3.max(t4));
+
+        // If tmax < 0, the ray's line intersects the box, but the whole box is behind the origin.
+        // If tmin > tmax, the ray doesn't intersect the box.
+        if (tmax < 0.) | (tmin > tmax) {
+            return None;
+        }
+
+        Some(tmin.max(0.))
+    }
+}
+
+impl IntersectsWith<AABB> for Ray {
+    /// Tests whether this [`Ray`] intersects with an [`AABB`].
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    #[inline]
+    fn intersects_with(&self, other: &AABB) -> bool {
+        self.entry_t(other).is_some()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ray_box_intersection_works() {
+        let r#box = AABB::new(-5, -5, 5, 5);
+        let ray_in_box_pointing_up = Ray::new(0., 0., 0., 1.);
+        let ray_on_left_pointing_right = Ray::new(-10., 0., 1., 0.);
+        let ray_on_right_pointing_right = Ray::new(10., 0., 1., 0.);
+        let ray_on_right_pointing_left = Ray::new(10., 0., -1., 0.);
+        let ray_on_top_pointing_right = Ray::new(10., -10., 1., 0.);
+        let ray_on_bottom_pointing_right = Ray::new(10., 10., 1., 0.);
+        assert!(ray_in_box_pointing_up.intersects_with(&r#box));
+        assert!(ray_on_left_pointing_right.intersects_with(&r#box));
+        assert!(ray_on_right_pointing_left.intersects_with(&r#box));
+        assert!(!ray_on_right_pointing_right.intersects_with(&r#box));
+        assert!(!ray_on_top_pointing_right.intersects_with(&r#box));
+        assert!(!ray_on_bottom_pointing_right.intersects_with(&r#box));
+
+        let diagonal_ray = Ray::new(-10., 0., 1., 0.1);
+        assert!(diagonal_ray.intersects_with(&r#box));
+    }
+
+    #[test]
+    fn entry_t_works() {
+        let r#box = AABB::new(-5, -5, 5, 5);
+        assert_eq!(Ray::new(-10., 0., 1., 0.).entry_t(&r#box), Some(5.));
+        assert_eq!(Ray::new(-10., 0., 2., 0.).entry_t(&r#box), Some(2.5));
+        assert_eq!(Ray::new(0., 0., 0., 1.).entry_t(&r#box), Some(0.));
+        assert_eq!(Ray::new(10., 0., 1., 0.).entry_t(&r#box), None);
+    }
+}
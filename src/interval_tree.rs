@@ -1,18 +1,29 @@
 //! According to Wikipedia:
 //! > An interval tree is a tree data structure to hold intervals.
 //! > Specifically, it allows one to efficiently find all intervals that overlap with any given interval or point.
+mod endpoint;
+mod error;
 mod inorder_iterator;
 mod interval;
 mod interval_tree_entry;
 mod interval_tree_node;
 mod interval_type;
+mod range_iterator;
+#[cfg(feature = "serde")]
+mod serialization;
+mod static_index;
 
+pub use endpoint::Endpoint;
+pub use error::InsertError;
 pub use inorder_iterator::InorderIterator;
 pub use interval::{Interval, IntervalType};
 pub use interval_tree_entry::IntervalTreeEntry;
+pub use static_index::StaticIntervalIndex;
 
 use crate::interval_tree::interval_tree_node::{IntervalTreeNode, IntervalTreeNodeOption};
+use crate::interval_tree::range_iterator::RangeIterator;
 use std::fmt::{Debug, Formatter};
+use std::ops::{Add, Sub};
 
 /// An Interval Tree.
 pub struct IntervalTree<T, D>
@@ -71,9 +82,16 @@ where
 
     /// Inserts a new entry to the `IntervalTree`.
     ///
+    /// # Remarks
+    /// Intervals whose bounds are not a number (`NaN`) are not supported, since they
+    /// break the ordering of the tree. Use [`try_insert`](Self::try_insert) to reject them.
+    ///
     /// # Parameters
     /// * `entry` - The entry to insert.
     ///
+    /// # Panics
+    /// In debug builds, panics if a bound of the interval is not a number.
+    ///
     /// # Example
     /// ```rust
     /// use space_partitioning::IntervalTree;
@@ -85,7 +103,12 @@ where
     where
         I: Into<IntervalTreeEntry<T, D>>,
     {
-        let node = IntervalTreeNode::new(entry.into());
+        let entry = entry.into();
+        debug_assert!(
+            !entry.interval.has_nan_bound(),
+            "the interval has a bound that is not a number"
+        );
+        let node = IntervalTreeNode::new(entry);
         if self.root.is_none() {
             self.root = Some(node);
         } else {
@@ -94,6 +117,65 @@ where
         self
     }
 
+    /// Inserts a new entry to the `IntervalTree` unless a bound of its interval
+    /// is not a number (`NaN`), which would break the ordering of the tree.
+    ///
+    /// # Parameters
+    /// * `entry` - The entry to insert.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::InsertError;
+    /// use space_partitioning::IntervalTree;
+    /// let mut tree = IntervalTree::default();
+    /// assert!(tree.try_insert((1.5..=2.0, "A")).is_ok());
+    /// assert_eq!(tree.try_insert((f64::NAN..=2.0, "B")).unwrap_err(), InsertError::NanBound);
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn try_insert<I>(&mut self, entry: I) -> Result<&Self, InsertError>
+    where
+        I: Into<IntervalTreeEntry<T, D>>,
+    {
+        let entry = entry.into();
+        if entry.interval.has_nan_bound() {
+            return Err(InsertError::NanBound);
+        }
+        Ok(self.insert(entry))
+    }
+
+    /// Inserts a new entry to the `IntervalTree` unless an entry with exactly
+    /// the same interval (i.e., the same start and end) is already stored.
+    ///
+    /// # Parameters
+    /// * `entry` - The entry to insert.
+    ///
+    /// # Returns
+    /// `true` if the entry was inserted, `false` if it was rejected as a duplicate.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// let mut tree = IntervalTree::default();
+    /// assert!(tree.insert_unique((15..=20, "A")));
+    /// assert!(!tree.insert_unique((15..=20, "B")));
+    /// assert!(tree.insert_unique((15..=21, "C")));
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn insert_unique<I>(&mut self, entry: I) -> bool
+    where
+        I: Into<IntervalTreeEntry<T, D>>,
+    {
+        let entry = entry.into();
+        if let Some(node) = &self.root {
+            if node.get(&entry.interval).is_some() {
+                return false;
+            }
+        }
+
+        self.insert(entry);
+        true
+    }
+
     /// Returns the number of elements in the `IntervalTree`.
     ///
     /// # Example
@@ -110,6 +192,32 @@ where
         };
     }
 
+    /// Returns the height of the `IntervalTree`, i.e., the number of nodes on the
+    /// longest path from the root to a leaf. An empty tree has a height of zero.
+    ///
+    /// # Remarks
+    /// The tree is not self-balancing; inserting intervals in order of their
+    /// starts results in a height equal to the number of elements.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([15..=20, 10..=30, 17..=19]);
+    /// assert_eq!(tree.height(), 2);
+    ///
+    /// let degenerate = IntervalTree::from_iter([10..=30, 15..=20, 17..=19]);
+    /// assert_eq!(degenerate.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize {
+        if let Some(node) = &self.root {
+            node.height()
+        } else {
+            0
+        }
+    }
+
     /// Returns whether the tree is empty, i.e., whether it has no elements.
     ///
     /// # Example
@@ -160,6 +268,197 @@ where
         }
     }
 
+    /// Returns all entries whose intervals overlap with the specified `interval`,
+    /// in no particular order.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    ///
+    /// let mut matches: Vec<_> = tree.overlap_search_all(18..=25).iter().map(|e| e.data).collect();
+    /// matches.sort();
+    /// assert_eq!(matches, ["A", "B"]);
+    /// ```
+    pub fn overlap_search_all<I>(&self, interval: I) -> Vec<&IntervalTreeEntry<T, D>>
+    where
+        I: Into<Interval<T>>,
+    {
+        let mut result = Vec::new();
+        if let Some(node) = &self.root {
+            node.collect_overlapping(&interval.into(), &mut result);
+        }
+        result
+    }
+
+    /// Folds the data of all entries whose intervals overlap with the specified
+    /// `interval`, in no particular order, e.g. to sum up the costs of all overlapping
+    /// bookings without collecting them first.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to query for.
+    /// * `init` - The initial value of the accumulator.
+    /// * `add` - The function combining the accumulator with the data of an entry.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, 3), (10..=30, 5), (30..=40, 7)]);
+    ///
+    /// assert_eq!(tree.sum_overlapping(18..=25, 0, |acc, cost| acc + cost), 8);
+    /// assert_eq!(tree.sum_overlapping(18..=25, 0, |acc, _| acc + 1), 2);
+    /// ```
+    pub fn sum_overlapping<I, S, F>(&self, interval: I, init: S, mut add: F) -> S
+    where
+        I: Into<Interval<T>>,
+        F: FnMut(S, &D) -> S,
+    {
+        let mut acc = Some(init);
+        if let Some(node) = &self.root {
+            let mut to_process = Vec::new();
+            node.for_each_overlapping_with(&interval.into(), &mut to_process, |entry| {
+                acc = acc.take().map(|acc| add(acc, &entry.data));
+            });
+        }
+        acc.expect("the accumulator is always restored")
+    }
+
+    /// Returns, for each of the specified `queries`, all entries whose intervals
+    /// overlap with the query, in no particular order. This is equivalent to calling
+    /// [`overlap_search_all`](Self::overlap_search_all) for each query, but shares the
+    /// traversal state between queries.
+    ///
+    /// # Parameters
+    /// * `queries` - The intervals to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    ///
+    /// let results = tree.overlap_search_all_batch(&[Interval::from(0..=5), Interval::from(35..=50)]);
+    /// assert_eq!(results.len(), 2);
+    /// assert!(results[0].is_empty());
+    /// assert_eq!(results[1][0].data, "C");
+    /// ```
+    pub fn overlap_search_all_batch(
+        &self,
+        queries: &[Interval<T>],
+    ) -> Vec<Vec<&IntervalTreeEntry<T, D>>> {
+        let mut to_process = Vec::new();
+        queries
+            .iter()
+            .map(|query| {
+                let mut result = Vec::new();
+                if let Some(node) = &self.root {
+                    node.collect_overlapping_with(query, &mut to_process, &mut result);
+                }
+                result
+            })
+            .collect()
+    }
+
+    /// Queries the tree for overlaps with the specified `interval` by linearly scanning
+    /// all entries in order of their interval starts.
+    ///
+    /// # Remarks
+    /// This method is meant to serve as a reference for validating the results of
+    /// [`overlap_search`](Self::overlap_search) and
+    /// [`overlap_search_all`](Self::overlap_search_all), e.g. in property tests.
+    /// Since it visits all entries, it should not be used otherwise.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (100..=101, "B")]);
+    /// let query = Interval::from(18..=25);
+    /// assert_eq!(tree.overlap_search_naive(&query).unwrap().data, "A");
+    /// assert!(tree.overlap_search(query).is_some());
+    /// ```
+    pub fn overlap_search_naive(&self, interval: &Interval<T>) -> Option<&IntervalTreeEntry<T, D>> {
+        self.iter_inorder()
+            .find(|entry| entry.interval.overlaps_with(interval))
+    }
+
+    /// Returns all entries whose intervals are entirely contained in the
+    /// specified `range`, i.e. that start no earlier and end no later than it.
+    /// The entries are returned in order of their interval starts.
+    ///
+    /// # Remarks
+    /// Unlike an overlap search, intervals that only partially overlap the
+    /// range are not returned.
+    ///
+    /// # Parameters
+    /// * `range` - The range to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (17..=19, "C")]);
+    ///
+    /// let contained = tree.contained_in(12..=20);
+    /// assert_eq!(contained.len(), 2);
+    /// assert_eq!(contained[0].data, "A");
+    /// assert_eq!(contained[1].data, "C");
+    /// ```
+    pub fn contained_in<I>(&self, range: I) -> Vec<&IntervalTreeEntry<T, D>>
+    where
+        I: Into<Interval<T>>,
+    {
+        let mut result = Vec::new();
+        if let Some(node) = &self.root {
+            node.collect_contained_in(&range.into(), &mut result);
+        }
+        result
+    }
+
+    /// Gets a mutable reference to the data of the entry whose interval
+    /// exactly equals the specified `interval`.
+    ///
+    /// # Remarks
+    /// This is an exact-key lookup, not an overlap search: both the start and the
+    /// end of the stored interval must equal the ones of the key.
+    /// If the same interval was inserted multiple times, the data of the
+    /// earliest inserted entry is returned.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to look up.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    ///
+    /// let mut tree = IntervalTree::new_from_entry((15..=20, 1));
+    /// tree.insert((10..=30, 2));
+    ///
+    /// *tree.get_mut(&Interval::from(15..=20)).unwrap() += 10;
+    /// assert_eq!(tree.overlap_search(15..=15).unwrap().data, 11);
+    ///
+    /// assert!(tree.get_mut(&Interval::from(15..=21)).is_none());
+    /// ```
+    pub fn get_mut(&mut self, interval: &Interval<T>) -> Option<&mut D> {
+        self.root.as_mut().and_then(|node| node.get_mut(interval))
+    }
+
     /// Returns an `InorderIterator<T, D>` that iterates the tree elements in order
     /// of their interval starts.
     ///
@@ -190,61 +489,428 @@ where
             InorderIterator::empty()
         }
     }
-}
 
-impl<T, D> Debug for IntervalTree<T, D>
-where
-    T: Debug + IntervalType,
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "len = {}", self.len())
+    /// Iterates the entries whose interval starts lie within the specified `range`,
+    /// in order of their starts.
+    ///
+    /// # Remarks
+    /// This is a range scan over the starts, not an overlap search: entries
+    /// starting before the range are not returned even if they overlap it.
+    /// Subtrees whose starts lie outside of the range are not visited.
+    ///
+    /// # Parameters
+    /// * `range` - The range of starts to iterate, including its boundaries.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (17..=19, "C")]);
+    ///
+    /// let data: Vec<_> = tree.iter_range(Interval::from(12..=17)).map(|entry| entry.data).collect();
+    /// assert_eq!(data, ["A", "C"]);
+    /// ```
+    pub fn iter_range(&self, range: Interval<T>) -> impl Iterator<Item = &IntervalTreeEntry<T, D>> {
+        RangeIterator::new(self.root.as_ref(), range)
     }
-}
 
-impl<T> From<Interval<T>> for IntervalTree<T, ()>
-where
-    T: IntervalType,
-{
-    fn from(interval: Interval<T>) -> Self {
-        Self::new_from_node(IntervalTreeNode::new_from_pair(interval, ()))
+    /// Rebuilds the tree such that it is balanced, i.e. has a height logarithmic
+    /// in the number of entries. All entries and their data are preserved.
+    ///
+    /// # Remarks
+    /// The tree is not self-balancing; inserting intervals in order of their
+    /// starts results in a degenerate tree with linear query times. Call this
+    /// method after many such inserts.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let mut tree = IntervalTree::from_iter((0..15).map(|start| start..=start + 1));
+    /// assert_eq!(tree.height(), 15);
+    ///
+    /// tree.rebalance();
+    /// assert_eq!(tree.height(), 4);
+    /// assert_eq!(tree.len(), 15);
+    /// ```
+    pub fn rebalance(&mut self) {
+        if let Some(root) = self.root.take() {
+            self.root = IntervalTreeNode::from_sorted(root.into_inorder());
+        }
     }
-}
 
-impl<T, D> From<(Interval<T>, D)> for IntervalTree<T, D>
-where
-    T: IntervalType,
-{
-    fn from(value: (Interval<T>, D)) -> Self {
-        Self::new_from_node(IntervalTreeNode::new_from_pair(value.0, value.1))
+    /// Removes all entries whose intervals overlap with the specified `interval`,
+    /// e.g. to clear a time window, and returns them in order of their interval starts.
+    ///
+    /// # Remarks
+    /// If any entry is removed, the remaining entries are rebuilt into a balanced
+    /// tree as if by [`rebalance`](Self::rebalance). Otherwise, the tree is unchanged.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to clear.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let mut tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    ///
+    /// let removed = tree.remove_all_overlapping(&(18..=25).into());
+    /// let removed: Vec<_> = removed.iter().map(|e| e.data).collect();
+    /// assert_eq!(removed, ["B", "A"]);
+    /// assert_eq!(tree.len(), 1);
+    /// assert!(tree.overlap_search(18..=25).is_none());
+    /// ```
+    pub fn remove_all_overlapping(
+        &mut self,
+        interval: &Interval<T>,
+    ) -> Vec<IntervalTreeEntry<T, D>> {
+        let entries = match self.root.take() {
+            Some(root) if root.overlap_search(interval).is_some() => root.into_inorder(),
+            root => {
+                self.root = root;
+                return Vec::new();
+            }
+        };
+
+        let (removed, remaining): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|entry| entry.interval.overlaps_with(interval));
+        self.root = IntervalTreeNode::from_sorted(remaining);
+        removed
     }
-}
 
-impl<I, T, D> std::iter::FromIterator<I> for IntervalTree<T, D>
-where
-    I: Into<IntervalTreeEntry<T, D>>,
-    T: IntervalType,
-{
-    fn from_iter<Iter>(iter: Iter) -> Self
+    /// Consumes the tree and returns a tree with the same intervals and the same
+    /// structure whose data is transformed by the specified function, e.g. to turn
+    /// raw parsed data into processed handles without building the tree again.
+    ///
+    /// # Remarks
+    /// The function is called once per entry, in order of the interval starts.
+    ///
+    /// # Parameters
+    /// * `f` - The function transforming the data of each entry.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, 1), (10..=30, 2)]);
+    /// let tree = tree.map_data(|data| format!("#{}", data));
+    /// assert_eq!(tree.overlap_search(25..=25).unwrap().data, "#2");
+    /// ```
+    pub fn map_data<E, F>(mut self, mut f: F) -> IntervalTree<T, E>
     where
-        Iter: IntoIterator<Item = I>,
+        F: FnMut(&D) -> E,
     {
-        match IntervalTreeNodeOption::from_iter(iter) {
-            IntervalTreeNodeOption::Some(node) => IntervalTree::new_from_node(node),
-            IntervalTreeNodeOption::None => IntervalTree::default(),
+        IntervalTree {
+            root: self.root.take().map(|root| root.map_data(&mut f)),
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::iter::FromIterator;
+    /// Consumes the tree and builds an immutable [`StaticIntervalIndex`] from its entries.
+    ///
+    /// # Remarks
+    /// The index stores the entries in a flat array sorted by interval start and
+    /// answers range queries without chasing pointers between nodes. Use it when
+    /// the set of intervals no longer changes and queries dominate.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    /// let index = tree.into_static();
+    ///
+    /// assert_eq!(index.len(), 3);
+    /// assert_eq!(index.stab(35)[0].data, "C");
+    /// ```
+    pub fn into_static(mut self) -> StaticIntervalIndex<T, D> {
+        let entries = match self.root.take() {
+            Some(root) => root.into_inorder(),
+            None => Vec::new(),
+        };
+        StaticIntervalIndex::from_sorted(entries)
+    }
 
-    mod construction {
-        use super::*;
+    /// Formats all entries in order of their interval starts, one entry per line.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(18..=25, "abc"), (0..=20, "xyz")]);
+    /// assert_eq!(
+    ///     tree.format_inorder(),
+    ///     "[0, 20] data = \"xyz\"\n[18, 25] data = \"abc\""
+    /// );
+    /// ```
+    pub fn format_inorder(&self) -> String
+    where
+        T: Debug,
+        D: Debug,
+    {
+        self.iter_inorder()
+            .map(|entry| format!("{:?}", entry))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
-        #[test]
-        fn insert_only_works() {
+impl<T, D> IntervalTree<T, D>
+where
+    T: IntervalType + Sub<Output = T>,
+{
+    /// Returns the earliest gap of at least `min_len` within the specified range
+    /// that is not covered by any stored interval. The full gap is returned,
+    /// i.e. it extends up to the next stored interval or the end of the range.
+    ///
+    /// # Remarks
+    /// Since intervals are closed, the gap is a touching gap: it is bounded by the
+    /// ends of the surrounding intervals and shares its endpoints with them, just
+    /// like back-to-back appointments do. Only the interior of the gap is free, and
+    /// [`overlap_search()`](Self::overlap_search) reports the surrounding intervals
+    /// as overlapping the gap.
+    ///
+    /// # Parameters
+    /// * `within` - The range to search in.
+    /// * `min_len` - The minimum length of the gap.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([9..=12, 15..=17]);
+    /// let within = Interval::from(8..=20);
+    ///
+    /// assert_eq!(tree.first_gap(within, 1), Some(Interval::from(8..=9)));
+    /// assert_eq!(tree.first_gap(within, 2), Some(Interval::from(12..=15)));
+    /// assert_eq!(tree.first_gap(within, 4), None);
+    ///
+    /// // The gap touches the surrounding intervals.
+    /// assert_eq!(tree.overlap_search_all(12..=15).len(), 2);
+    /// ```
+    pub fn first_gap(&self, within: Interval<T>, min_len: T) -> Option<Interval<T>> {
+        let mut booked = self.overlap_search_all(within.clone());
+        booked.sort_by(|a, b| {
+            a.interval
+                .start
+                .partial_cmp(&b.interval.start)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        // The start of the range not yet known to be booked.
+        let mut cursor = within.start;
+        for entry in booked {
+            let interval = &entry.interval;
+            if interval.start > cursor {
+                let end = if interval.start < within.end {
+                    interval.start.clone()
+                } else {
+                    within.end.clone()
+                };
+                if end.clone() - cursor.clone() >= min_len {
+                    return Some(Interval::new(cursor, end));
+                }
+            }
+
+            if interval.end > cursor {
+                cursor = interval.end.clone();
+            }
+        }
+
+        if cursor <= within.end && within.end.clone() - cursor.clone() >= min_len {
+            Some(Interval::new(cursor, within.end))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the shortest interval containing the specified `point`, e.g. to find
+    /// the innermost of several nested scopes.
+    ///
+    /// # Remarks
+    /// If multiple intervals of the same length contain the point, any of them is returned.
+    ///
+    /// # Parameters
+    /// * `point` - The point to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(0..=100, "file"), (10..=50, "fn"), (20..=30, "loop")]);
+    ///
+    /// assert_eq!(tree.innermost_containing(25).unwrap().data, "loop");
+    /// assert_eq!(tree.innermost_containing(40).unwrap().data, "fn");
+    /// assert_eq!(tree.innermost_containing(75).unwrap().data, "file");
+    /// assert!(tree.innermost_containing(101).is_none());
+    /// ```
+    pub fn innermost_containing(&self, point: T) -> Option<&IntervalTreeEntry<T, D>> {
+        let mut innermost: Option<(&IntervalTreeEntry<T, D>, T)> = None;
+        for entry in self.overlap_search_all(Interval::new(point.clone(), point)) {
+            let len = entry.interval.end.clone() - entry.interval.start.clone();
+            match &innermost {
+                Some((_, shortest)) if len >= *shortest => {}
+                _ => innermost = Some((entry, len)),
+            }
+        }
+        innermost.map(|(entry, _)| entry)
+    }
+}
+
+impl<T, D> IntervalTree<T, D>
+where
+    T: IntervalType + Add<Output = T> + Sub<Output = T>,
+{
+    /// Returns the total length of the specified range that is covered by at least
+    /// one stored interval, e.g. to determine how much of a time window is booked.
+    ///
+    /// # Remarks
+    /// Stored intervals are clamped to the range, and overlapping intervals are
+    /// merged such that no part of the range is counted twice.
+    ///
+    /// # Parameters
+    /// * `within` - The range to measure the coverage of.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use space_partitioning::interval_tree::Interval;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([0..=6, 4..=8, 15..=30]);
+    /// assert_eq!(tree.covered_length(Interval::from(0..=20)), 13);
+    /// ```
+    pub fn covered_length(&self, within: Interval<T>) -> T {
+        let mut booked = self.overlap_search_all(within.clone());
+        booked.sort_by(|a, b| {
+            a.interval
+                .start
+                .partial_cmp(&b.interval.start)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let mut total = within.start.clone() - within.start.clone();
+
+        // The range covered by the current run of overlapping intervals.
+        let mut run: Option<(T, T)> = None;
+        for entry in booked {
+            let interval = &entry.interval;
+            let start = if interval.start > within.start {
+                interval.start.clone()
+            } else {
+                within.start.clone()
+            };
+            let end = if interval.end < within.end {
+                interval.end.clone()
+            } else {
+                within.end.clone()
+            };
+
+            match run.as_mut() {
+                Some((_, run_end)) if start <= *run_end => {
+                    if end > *run_end {
+                        *run_end = end;
+                    }
+                }
+                _ => {
+                    if let Some((run_start, run_end)) = run.replace((start, end)) {
+                        total = total + (run_end - run_start);
+                    }
+                }
+            }
+        }
+
+        if let Some((run_start, run_end)) = run {
+            total = total + (run_end - run_start);
+        }
+        total
+    }
+}
+
+impl<T, D> Drop for IntervalTree<T, D>
+where
+    T: IntervalType,
+{
+    /// Drops the nodes one by one, since the default recursive drop
+    /// may overflow the stack for degenerate (i.e., very deep) trees.
+    fn drop(&mut self) {
+        let mut to_drop = Vec::new();
+        if let Some(root) = &mut self.root {
+            to_drop.extend(root.left.take());
+            to_drop.extend(root.right.take());
+        }
+
+        while let Some(mut node) = to_drop.pop() {
+            to_drop.extend(node.left.take());
+            to_drop.extend(node.right.take());
+        }
+    }
+}
+
+impl<T, D> Debug for IntervalTree<T, D>
+where
+    T: Debug + IntervalType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "len = {}", self.len())
+    }
+}
+
+impl<T> From<Interval<T>> for IntervalTree<T, ()>
+where
+    T: IntervalType,
+{
+    fn from(interval: Interval<T>) -> Self {
+        Self::new_from_node(IntervalTreeNode::new_from_pair(interval, ()))
+    }
+}
+
+impl<T, D> From<(Interval<T>, D)> for IntervalTree<T, D>
+where
+    T: IntervalType,
+{
+    fn from(value: (Interval<T>, D)) -> Self {
+        Self::new_from_node(IntervalTreeNode::new_from_pair(value.0, value.1))
+    }
+}
+
+impl<I, T, D> std::iter::FromIterator<I> for IntervalTree<T, D>
+where
+    I: Into<IntervalTreeEntry<T, D>>,
+    T: IntervalType,
+{
+    fn from_iter<Iter>(iter: Iter) -> Self
+    where
+        Iter: IntoIterator<Item = I>,
+    {
+        match IntervalTreeNodeOption::from_iter(iter) {
+            IntervalTreeNodeOption::Some(node) => IntervalTree::new_from_node(node),
+            IntervalTreeNodeOption::None => IntervalTree::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::iter::FromIterator;
+
+    mod construction {
+        use super::*;
+
+        #[test]
+        fn insert_only_works() {
             let mut tree = IntervalTree::default();
             assert_eq!(tree.len(), 0);
             tree.insert((15..=20, 4.2));
@@ -253,6 +919,28 @@ mod test {
             assert_eq!(tree.len(), 2);
         }
 
+        #[test]
+        fn insert_unique_rejects_duplicates() {
+            let mut tree = IntervalTree::from_iter([(15..=20, 1), (10..=30, 2), (17..=19, 3)]);
+            assert!(!tree.insert_unique((15..=20, 4)));
+            assert!(!tree.insert_unique((17..=19, 5)));
+            assert_eq!(tree.len(), 3);
+
+            // The original data is retained.
+            assert_eq!(*tree.get_mut(&Interval::from(15..=20)).unwrap(), 1);
+            assert_eq!(*tree.get_mut(&Interval::from(17..=19)).unwrap(), 3);
+        }
+
+        #[test]
+        fn insert_unique_accepts_distinct_intervals() {
+            let mut tree = IntervalTree::default();
+            assert!(tree.insert_unique((15..=20, 1)));
+            assert!(tree.insert_unique((15..=25, 2)));
+            assert!(tree.insert_unique((10..=20, 3)));
+            assert!(tree.insert_unique((20..=20, 4)));
+            assert_eq!(tree.len(), 4);
+        }
+
         #[test]
         fn from_constructor_works() {
             let mut tree = IntervalTree::new_from_entry(15..=20);
@@ -327,6 +1015,42 @@ mod test {
         }
     }
 
+    mod format {
+        use super::*;
+
+        #[test]
+        fn format_inorder_works() {
+            let tree = IntervalTree::from_iter([
+                (15..=20, "now"),
+                (10..=30, "data"),
+                (17..=19, "correctly"),
+                (5..=20, "this"),
+                (12..=15, "is"),
+                (30..=40, "ordered"),
+            ]);
+
+            let formatted = tree.format_inorder();
+            let lines: Vec<_> = formatted.lines().collect();
+            assert_eq!(
+                lines,
+                [
+                    "[5, 20] data = \"this\"",
+                    "[10, 30] data = \"data\"",
+                    "[12, 15] data = \"is\"",
+                    "[15, 20] data = \"now\"",
+                    "[17, 19] data = \"correctly\"",
+                    "[30, 40] data = \"ordered\"",
+                ]
+            );
+        }
+
+        #[test]
+        fn format_inorder_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            assert_eq!(tree.format_inorder(), "");
+        }
+    }
+
     mod search {
         use super::*;
 
@@ -337,6 +1061,697 @@ mod test {
             let overlap = tree.overlap_search(Interval::from(6..=7));
             assert_eq!(overlap.unwrap().interval, Interval::from(5..=20));
         }
+
+        #[test]
+        fn degenerate_tree_does_not_overflow_the_stack() {
+            // Inserting in order of the starts builds a chain of right children.
+            // Each insert walks the entire chain, so the number of intervals is
+            // kept just large enough to overflow a recursive implementation.
+            const N: i32 = 30_000;
+            let mut tree = IntervalTree::default();
+            for start in 0..N {
+                tree.insert((start..=start + 1, ()));
+            }
+            assert_eq!(tree.len(), N as usize);
+            assert_eq!(tree.height(), N as usize);
+
+            let overlap = tree.overlap_search(N..=N + 10);
+            assert_eq!(overlap.unwrap().interval, Interval::from(N - 1..=N));
+            assert!(tree.overlap_search(N + 1..=N + 10).is_none());
+
+            assert!(tree.get_mut(&Interval::from(N - 1..=N)).is_some());
+            assert_eq!(tree.contained_in(N - 3..=N).len(), 3);
+        }
+    }
+
+    mod oracle {
+        use super::*;
+        use proptest::prelude::*;
+
+        #[test]
+        fn overlap_search_explores_subtree_max() {
+            // The end of the last interval needs to propagate to the root's left child.
+            let tree = IntervalTree::from_iter([10..=12, 5..=6, 7..=50]);
+            let query = Interval::from(40..=41);
+            assert_eq!(
+                tree.overlap_search(query).unwrap().interval,
+                Interval::from(7..=50)
+            );
+            assert_eq!(tree.overlap_search_all(query).len(), 1);
+            assert!(tree.overlap_search_naive(&query).is_some());
+        }
+
+        fn sorted_intervals<'a, I>(entries: I) -> Vec<Interval<i32>>
+        where
+            I: IntoIterator<Item = &'a IntervalTreeEntry<i32, usize>>,
+        {
+            let mut intervals: Vec<_> = entries
+                .into_iter()
+                .map(|entry| (entry.data, entry.interval))
+                .collect();
+            intervals.sort_by_key(|(data, _)| *data);
+            intervals
+                .into_iter()
+                .map(|(_, interval)| interval)
+                .collect()
+        }
+
+        fn interval() -> impl Strategy<Value = Interval<i32>> {
+            (-100..100, 0..30).prop_map(|(start, length)| Interval::from(start..=start + length))
+        }
+
+        proptest! {
+            #[test]
+            fn overlap_search_matches_naive(
+                intervals in prop::collection::vec(interval(), 0..64),
+                query in interval()
+            ) {
+                let tree = IntervalTree::from_iter(
+                    intervals.iter().copied().enumerate().map(|(i, interval)| (interval, i)),
+                );
+
+                let expected = tree.overlap_search_naive(&query);
+                let actual = tree.overlap_search(query);
+                prop_assert_eq!(expected.is_some(), actual.is_some());
+                if let Some(actual) = actual {
+                    prop_assert!(actual.interval.overlaps_with(&query));
+                }
+
+                let expected_all = tree
+                    .iter_inorder()
+                    .filter(|entry| entry.interval.overlaps_with(&query));
+                prop_assert_eq!(
+                    sorted_intervals(tree.overlap_search_all(query)),
+                    sorted_intervals(expected_all)
+                );
+
+                let expected_all = sorted_intervals(tree.overlap_search_all(query));
+                let index = tree.into_static();
+                prop_assert_eq!(
+                    sorted_intervals(index.overlap_search_all(query)),
+                    expected_all
+                );
+            }
+        }
+    }
+
+    mod gap {
+        use super::*;
+
+        #[test]
+        fn first_gap_works() {
+            let tree = IntervalTree::from_iter([0..=10, 2..=4, 14..=20]);
+            let within = Interval::from(5..=18);
+            assert_eq!(tree.first_gap(within, 3), Some(Interval::from(10..=14)));
+            assert_eq!(tree.first_gap(within, 5), None);
+        }
+
+        #[test]
+        fn first_gap_touches_the_surrounding_intervals() {
+            let tree = IntervalTree::from_iter([(0..=10, 'A'), (2..=4, 'B'), (14..=20, 'C')]);
+            let gap = tree
+                .first_gap(Interval::from(5..=18), 3)
+                .expect("there should be a gap");
+
+            // Only the intervals sharing an endpoint with the gap overlap it.
+            let mut data: Vec<_> = tree
+                .overlap_search_all(gap.clone())
+                .iter()
+                .map(|entry| entry.data)
+                .collect();
+            data.sort_unstable();
+            assert_eq!(data, ['A', 'C']);
+
+            // The interior of the gap is free.
+            assert!(tree.overlap_search(gap.start + 1..=gap.end - 1).is_none());
+        }
+
+        #[test]
+        fn first_gap_when_fully_booked_works() {
+            let tree = IntervalTree::from_iter([0..=10, 8..=12, 12..=20]);
+            assert_eq!(tree.first_gap(Interval::from(2..=18), 1), None);
+            assert_eq!(tree.first_gap(Interval::from(2..=18), 0), None);
+        }
+
+        #[test]
+        fn first_gap_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            let within = Interval::from(2..=18);
+            assert_eq!(tree.first_gap(within, 16), Some(within));
+            assert_eq!(tree.first_gap(within, 17), None);
+        }
+
+        #[test]
+        fn first_gap_at_range_boundaries_works() {
+            let tree = IntervalTree::from_iter([(5..=8, 'A'), (20..=30, 'B')]);
+            assert_eq!(
+                tree.first_gap(Interval::from(0..=25), 5),
+                Some(Interval::from(0..=5))
+            );
+            assert_eq!(
+                tree.first_gap(Interval::from(0..=25), 6),
+                Some(Interval::from(8..=20))
+            );
+            assert_eq!(
+                tree.first_gap(Interval::from(10..=40), 10),
+                Some(Interval::from(10..=20))
+            );
+            assert_eq!(
+                tree.first_gap(Interval::from(22..=40), 10),
+                Some(Interval::from(30..=40))
+            );
+        }
+
+        #[test]
+        fn first_gap_with_floats_works() {
+            let tree = IntervalTree::from_iter([0.0..=1.5, 2.0..=3.0]);
+            assert_eq!(
+                tree.first_gap(Interval::from(0.0..=4.0), 0.5),
+                Some(Interval::from(1.5..=2.0))
+            );
+            assert_eq!(
+                tree.first_gap(Interval::from(0.0..=4.0), 0.75),
+                Some(Interval::from(3.0..=4.0))
+            );
+        }
+    }
+
+    mod nan {
+        use super::*;
+
+        #[test]
+        fn try_insert_rejects_nan_bounds() {
+            let mut tree = IntervalTree::from_iter([(0.0..=1.0, 'A'), (2.0..=3.0, 'B')]);
+            assert_eq!(
+                tree.try_insert((f64::NAN..=1.5, 'C')).unwrap_err(),
+                InsertError::NanBound
+            );
+            assert_eq!(
+                tree.try_insert((0.5..=f64::NAN, 'D')).unwrap_err(),
+                InsertError::NanBound
+            );
+            assert!(tree.try_insert((0.5..=2.5, 'E')).is_ok());
+
+            // The tree was not corrupted by the rejected intervals.
+            assert_eq!(tree.len(), 3);
+            let mut data: Vec<_> = tree
+                .overlap_search_all(1.5..=1.5)
+                .iter()
+                .map(|entry| entry.data)
+                .collect();
+            data.sort_unstable();
+            assert_eq!(data, ['E']);
+        }
+
+        #[test]
+        fn try_insert_rejects_nan_endpoints() {
+            let mut tree = IntervalTree::<Endpoint<f32>, ()>::default();
+            assert!(tree.try_insert((f32::NAN.., ())).is_err());
+            assert!(tree.try_insert((1.0.., ())).is_ok());
+            assert_eq!(tree.len(), 1);
+        }
+
+        #[test]
+        fn try_insert_rejects_nan_newtypes() {
+            #[derive(Debug, Clone, PartialEq, PartialOrd)]
+            struct Millis(f64);
+
+            impl IntervalType for Millis {}
+
+            let mut tree = IntervalTree::default();
+            assert_eq!(
+                tree.try_insert((Interval::new(Millis(f64::NAN), Millis(1.0)), ()))
+                    .unwrap_err(),
+                InsertError::NanBound
+            );
+            assert!(tree
+                .try_insert((Interval::new(Millis(0.0), Millis(1.0)), ()))
+                .is_ok());
+            assert_eq!(tree.len(), 1);
+        }
+
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic(expected = "the interval has a bound that is not a number")]
+        fn insert_nan_bound_panics() {
+            let mut tree = IntervalTree::default();
+            tree.insert((f32::NAN..=1.0, ()));
+        }
+    }
+
+    mod innermost {
+        use super::*;
+
+        #[test]
+        fn innermost_containing_works() {
+            let tree = IntervalTree::from_iter([
+                (0..=100, 'A'),
+                (10..=60, 'B'),
+                (20..=40, 'C'),
+                (25..=30, 'D'),
+                (50..=90, 'E'),
+            ]);
+
+            assert_eq!(tree.innermost_containing(27).unwrap().data, 'D');
+            assert_eq!(tree.innermost_containing(35).unwrap().data, 'C');
+            assert_eq!(tree.innermost_containing(45).unwrap().data, 'B');
+            assert_eq!(tree.innermost_containing(55).unwrap().data, 'E');
+            assert_eq!(tree.innermost_containing(95).unwrap().data, 'A');
+            assert_eq!(tree.innermost_containing(0).unwrap().data, 'A');
+            assert!(tree.innermost_containing(-1).is_none());
+        }
+
+        #[test]
+        fn innermost_containing_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            assert!(tree.innermost_containing(0).is_none());
+        }
+    }
+
+    mod coverage {
+        use super::*;
+
+        #[test]
+        fn covered_length_when_fully_covered_works() {
+            let tree = IntervalTree::from_iter([0..=10, 8..=12, 12..=20]);
+            assert_eq!(tree.covered_length(Interval::from(2..=18)), 16);
+        }
+
+        #[test]
+        fn covered_length_when_half_covered_works() {
+            let tree = IntervalTree::from_iter([0..=5]);
+            assert_eq!(tree.covered_length(Interval::from(0..=10)), 5);
+
+            let tree = IntervalTree::from_iter([0.0..=0.25, 0.5..=0.75]);
+            assert_eq!(tree.covered_length(Interval::from(0.0..=1.0)), 0.5);
+        }
+
+        #[test]
+        fn covered_length_does_not_count_overlaps_twice() {
+            let tree = IntervalTree::from_iter([0..=6, 2..=4, 4..=8, 3..=5]);
+            assert_eq!(tree.covered_length(Interval::from(0..=10)), 8);
+        }
+
+        #[test]
+        fn covered_length_clamps_to_range() {
+            let tree = IntervalTree::from_iter([-5..=3, 7..=15, -100..=-50]);
+            assert_eq!(tree.covered_length(Interval::from(0..=10)), 6);
+            assert_eq!(tree.covered_length(Interval::from(1..=2)), 1);
+        }
+
+        #[test]
+        fn covered_length_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            assert_eq!(tree.covered_length(Interval::from(0..=10)), 0);
+
+            let tree = IntervalTree::from_iter([20..=30]);
+            assert_eq!(tree.covered_length(Interval::from(0..=10)), 0);
+        }
+    }
+
+    mod rebalance {
+        use super::*;
+        use crate::interval_tree::interval_tree_node::test::construct_degenerate_chain;
+
+        #[test]
+        fn rebalance_works() {
+            let mut tree = IntervalTree::new_from_node(construct_degenerate_chain(1000));
+            assert_eq!(tree.height(), 1000);
+
+            tree.rebalance();
+            assert_eq!(tree.len(), 1000);
+            assert_eq!(tree.height(), 10);
+
+            let intervals: Vec<_> = tree.iter_inorder().map(|entry| entry.interval).collect();
+            let expected: Vec<_> = (0..1000).map(|i| Interval::from(i..=i + 1)).collect();
+            assert_eq!(intervals, expected);
+
+            // The maximum ends were propagated such that searches still work.
+            assert_eq!(
+                tree.overlap_search(1000..=1005).unwrap().interval,
+                Interval::from(999..=1000)
+            );
+            assert_eq!(tree.overlap_search_all(500..=500).len(), 2);
+            assert!(tree.overlap_search(1001..=1005).is_none());
+        }
+
+        #[test]
+        fn rebalance_preserves_data() {
+            let mut tree =
+                IntervalTree::from_iter((0..100).map(|start| (start..=start + 10, start * 2)));
+            tree.rebalance();
+
+            assert_eq!(tree.height(), 7);
+            for start in 0..100 {
+                let interval = Interval::from(start..=start + 10);
+                assert_eq!(*tree.get_mut(&interval).unwrap(), start * 2);
+            }
+        }
+
+        #[test]
+        fn rebalance_with_duplicate_starts_works() {
+            let mut tree = IntervalTree::from_iter([
+                (5..=6, 'A'),
+                (5..=9, 'B'),
+                (5..=6, 'C'),
+                (1..=2, 'D'),
+                (5..=7, 'E'),
+                (8..=9, 'F'),
+            ]);
+            tree.rebalance();
+
+            assert_eq!(tree.len(), 6);
+            assert_eq!(tree.overlap_search_all(9..=9).len(), 2);
+
+            // Duplicates still resolve to the earliest inserted entry.
+            assert_eq!(*tree.get_mut(&Interval::from(5..=6)).unwrap(), 'A');
+            assert_eq!(*tree.get_mut(&Interval::from(5..=7)).unwrap(), 'E');
+        }
+
+        #[test]
+        fn rebalance_when_empty_works() {
+            let mut tree = IntervalTree::<i32, ()>::default();
+            tree.rebalance();
+            assert!(tree.is_empty());
+        }
+    }
+
+    mod remove {
+        use super::*;
+
+        #[test]
+        fn remove_all_overlapping_works() {
+            let mut tree =
+                IntervalTree::from_iter((0..100).map(|start| (start * 10..=start * 10 + 5, start)));
+
+            let removed = tree.remove_all_overlapping(&(203..=421).into());
+            let removed: Vec<_> = removed.iter().map(|entry| entry.data).collect();
+            assert_eq!(removed, (20..=42).collect::<Vec<_>>());
+
+            assert_eq!(tree.len(), 77);
+            assert!(tree.overlap_search(203..=421).is_none());
+
+            let remaining: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+            let expected: Vec<_> = (0..20).chain(43..100).collect();
+            assert_eq!(remaining, expected);
+
+            // The maximum ends were rebuilt such that searches still work.
+            assert_eq!(tree.overlap_search(995..=1000).unwrap().data, 99);
+            assert_eq!(tree.overlap_search(0..=0).unwrap().data, 0);
+        }
+
+        #[test]
+        fn remove_all_overlapping_without_match_keeps_tree() {
+            let mut tree = IntervalTree::from_iter((0..10).map(|start| start..=start + 1));
+            assert_eq!(tree.height(), 10);
+
+            assert!(tree.remove_all_overlapping(&(20..=30).into()).is_empty());
+            assert_eq!(tree.len(), 10);
+            assert_eq!(tree.height(), 10);
+        }
+
+        #[test]
+        fn remove_all_overlapping_everything_works() {
+            let mut tree = IntervalTree::from_iter([(15..=20, 'A'), (10..=30, 'B')]);
+            assert_eq!(tree.remove_all_overlapping(&(0..=100).into()).len(), 2);
+            assert!(tree.is_empty());
+            assert!(tree.remove_all_overlapping(&(0..=100).into()).is_empty());
+        }
+    }
+
+    mod static_index {
+        use super::*;
+
+        #[test]
+        fn into_static_works() {
+            let tree = IntervalTree::from_iter([
+                (15..=20, 'A'),
+                (10..=30, 'B'),
+                (17..=19, 'C'),
+                (5..=20, 'D'),
+                (12..=15, 'E'),
+                (30..=40, 'F'),
+            ]);
+            let expected: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+
+            let index = tree.into_static();
+            assert_eq!(index.len(), 6);
+            let actual: Vec<_> = index.iter().map(|entry| entry.data).collect();
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn overlap_search_all_works() {
+            let index = IntervalTree::from_iter([
+                (15..=20, 'A'),
+                (10..=30, 'B'),
+                (17..=19, 'C'),
+                (5..=20, 'D'),
+                (12..=15, 'E'),
+                (30..=40, 'F'),
+            ])
+            .into_static();
+
+            let data = |entries: Vec<&IntervalTreeEntry<i32, char>>| {
+                entries.iter().map(|entry| entry.data).collect::<Vec<_>>()
+            };
+            assert_eq!(data(index.overlap_search_all(6..=7)), ['D']);
+            assert_eq!(
+                data(index.overlap_search_all(18..=25)),
+                ['D', 'B', 'A', 'C']
+            );
+            assert_eq!(data(index.overlap_search_all(41..=50)), []);
+            assert_eq!(data(index.stab(30)), ['B', 'F']);
+        }
+
+        #[test]
+        fn empty_index_works() {
+            let index = IntervalTree::<i32, ()>::default().into_static();
+            assert!(index.is_empty());
+            assert!(index.overlap_search_all(0..=10).is_empty());
+            assert!(index.stab(0).is_empty());
+        }
+
+        #[test]
+        fn degenerate_tree_does_not_overflow_the_stack() {
+            let tree = IntervalTree::new_from_node(
+                crate::interval_tree::interval_tree_node::test::construct_degenerate_chain(100_000),
+            );
+            let index = tree.into_static();
+            assert_eq!(index.len(), 100_000);
+            assert_eq!(index.stab(50_000).len(), 2);
+        }
+    }
+
+    mod sum {
+        use super::*;
+
+        #[test]
+        fn sum_overlapping_works() {
+            let tree = IntervalTree::from_iter(
+                [15..=20, 10..=30, 17..=19, 5..=20, 12..=15, 30..=40]
+                    .iter()
+                    .map(|range| (range.clone(), *range.end() - *range.start())),
+            );
+
+            for query in [18..=25, 0..=4, 0..=50, 15..=15, 31..=31] {
+                let expected: i32 = tree
+                    .overlap_search_all(query.clone())
+                    .iter()
+                    .map(|entry| entry.data)
+                    .sum();
+                assert_eq!(
+                    tree.sum_overlapping(query, 0, |acc, len| acc + len),
+                    expected
+                );
+            }
+
+            assert_eq!(
+                tree.sum_overlapping(18..=25, 0, |acc, len| acc + len),
+                5 + 20 + 2 + 15
+            );
+        }
+
+        #[test]
+        fn sum_overlapping_when_empty_works() {
+            let tree = IntervalTree::<i32, i32>::default();
+            assert_eq!(tree.sum_overlapping(0..=10, 42, |acc, data| acc + data), 42);
+        }
+    }
+
+    mod batch {
+        use super::*;
+
+        #[test]
+        fn overlap_search_all_batch_works() {
+            let tree = IntervalTree::from_iter([
+                (15..=20, 1),
+                (10..=30, 2),
+                (17..=19, 3),
+                (5..=20, 4),
+                (12..=15, 5),
+                (30..=40, 6),
+            ]);
+            let queries = [
+                Interval::from(0..=4),
+                Interval::from(6..=7),
+                Interval::from(16..=18),
+                Interval::from(30..=30),
+                Interval::from(0..=100),
+            ];
+
+            let results = tree.overlap_search_all_batch(&queries);
+            assert_eq!(results.len(), queries.len());
+            for (query, result) in queries.iter().zip(results) {
+                let mut batch: Vec<_> = result.iter().map(|entry| entry.data).collect();
+                let mut single: Vec<_> = tree
+                    .overlap_search_all(*query)
+                    .iter()
+                    .map(|entry| entry.data)
+                    .collect();
+                batch.sort_unstable();
+                single.sort_unstable();
+                assert_eq!(batch, single);
+            }
+        }
+
+        #[test]
+        fn overlap_search_all_batch_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            let results = tree.overlap_search_all_batch(&[Interval::from(0..=4)]);
+            assert_eq!(results.len(), 1);
+            assert!(results[0].is_empty());
+        }
+    }
+
+    mod contained_in {
+        use super::*;
+
+        #[test]
+        fn contained_in_works() {
+            let tree =
+                IntervalTree::from_iter([15..=20, 10..=30, 17..=19, 5..=20, 12..=15, 30..=40]);
+
+            // 5..=20 and 10..=30 overlap the range but aren't contained in it.
+            let contained: Vec<_> = tree
+                .contained_in(12..=20)
+                .into_iter()
+                .map(|entry| entry.interval)
+                .collect();
+            assert_eq!(
+                contained,
+                [
+                    Interval::from(12..=15),
+                    Interval::from(15..=20),
+                    Interval::from(17..=19)
+                ]
+            );
+            assert!(tree.overlap_search(12..=20).is_some());
+        }
+
+        #[test]
+        fn contained_in_includes_boundaries() {
+            let tree = IntervalTree::from_iter([15..=20, 10..=30, 30..=40]);
+            let contained = tree.contained_in(10..=40);
+            assert_eq!(contained.len(), 3);
+        }
+
+        #[test]
+        fn contained_in_without_match_works() {
+            let tree = IntervalTree::from_iter([15..=20, 10..=30, 30..=40]);
+            assert!(tree.contained_in(16..=35).is_empty());
+            assert!(tree.contained_in(50..=60).is_empty());
+        }
+
+        #[test]
+        fn contained_in_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            assert!(tree.contained_in(0..=10).is_empty());
+        }
+    }
+
+    mod range {
+        use super::*;
+
+        #[test]
+        fn iter_range_works() {
+            let tree = IntervalTree::from_iter(
+                [15..=20, 10..=30, 17..=19, 5..=20, 12..=15, 30..=40, 17..=18]
+                    .iter()
+                    .map(|range| (range.clone(), *range.start())),
+            );
+
+            // 5..=20 and 10..=30 overlap the range but start before it.
+            let starts: Vec<_> = tree
+                .iter_range(Interval::from(12..=17))
+                .map(|entry| entry.interval.start)
+                .collect();
+            assert_eq!(starts, [12, 15, 17, 17]);
+        }
+
+        #[test]
+        fn iter_range_matches_filtered_inorder_iteration() {
+            let tree = IntervalTree::from_iter((0..50).map(|i| (i * 7) % 50..=(i * 7) % 50 + 3));
+            for (start, end) in [(0, 49), (10, 20), (-5, 3), (48, 60), (21, 21), (30, 10)] {
+                let expected: Vec<_> = tree
+                    .iter_inorder()
+                    .filter(|entry| entry.interval.start >= start && entry.interval.start <= end)
+                    .collect();
+                let actual: Vec<_> = tree.iter_range(Interval::new(start, end)).collect();
+                assert_eq!(actual.len(), expected.len());
+                for (a, e) in actual.into_iter().zip(expected) {
+                    assert!(std::ptr::eq(a, e));
+                }
+            }
+        }
+
+        #[test]
+        fn iter_range_when_empty_works() {
+            let tree = IntervalTree::<i32, ()>::default();
+            assert!(tree.iter_range(Interval::from(0..=10)).next().is_none());
+        }
+    }
+
+    mod lookup {
+        use super::*;
+
+        #[test]
+        fn get_mut_works() {
+            let mut tree = IntervalTree::from_iter([
+                (15..=20, 1),
+                (10..=30, 2),
+                (17..=19, 3),
+                (5..=20, 4),
+                (12..=15, 5),
+                (30..=40, 6),
+            ]);
+
+            *tree.get_mut(&Interval::from(17..=19)).unwrap() += 100;
+            *tree.get_mut(&Interval::from(30..=40)).unwrap() += 100;
+
+            let data: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+            assert_eq!(data, [4, 2, 5, 1, 103, 106]);
+        }
+
+        #[test]
+        fn get_mut_requires_exact_match() {
+            let mut tree = IntervalTree::from_iter([(15..=20, 1), (15..=25, 2)]);
+            assert!(tree.get_mut(&Interval::from(15..=22)).is_none());
+            assert!(tree.get_mut(&Interval::from(16..=20)).is_none());
+            assert_eq!(*tree.get_mut(&Interval::from(15..=25)).unwrap(), 2);
+        }
+
+        #[test]
+        fn get_mut_with_duplicates_returns_first_inserted() {
+            let mut tree = IntervalTree::from_iter([(15..=20, 1), (15..=20, 2)]);
+            assert_eq!(*tree.get_mut(&Interval::from(15..=20)).unwrap(), 1);
+        }
+
+        #[test]
+        fn get_mut_when_empty_works() {
+            let mut tree = IntervalTree::<i32, ()>::default();
+            assert!(tree.get_mut(&Interval::from(15..=20)).is_none());
+        }
     }
 
     mod utility {
@@ -357,6 +1772,204 @@ mod test {
         }
     }
 
+    mod map_data {
+        use super::*;
+        use crate::interval_tree::interval_tree_node::test::construct_degenerate_chain;
+
+        #[test]
+        fn map_data_works() {
+            let mut tree = IntervalTree::from_iter([
+                (15..=20, 0),
+                (10..=30, 1),
+                (17..=19, 2),
+                (5..=20, 3),
+                (12..=15, 4),
+                (30..=40, 5),
+            ]);
+            tree.insert((12..=14, 6));
+            let height = tree.height();
+            let inorder: Vec<_> = tree
+                .iter_inorder()
+                .map(|entry| (entry.interval, entry.data))
+                .collect();
+
+            let mut visited = Vec::new();
+            let mapped = tree.map_data(|data| {
+                visited.push(*data);
+                format!("entry {}", data)
+            });
+
+            // The function is called in order of the interval starts.
+            assert_eq!(visited, [3, 1, 4, 6, 0, 2, 5]);
+
+            assert_eq!(mapped.len(), 7);
+            assert_eq!(mapped.height(), height);
+            let mapped_inorder: Vec<_> = mapped
+                .iter_inorder()
+                .map(|entry| (entry.interval, entry.data.clone()))
+                .collect();
+            let expected: Vec<_> = inorder
+                .into_iter()
+                .map(|(interval, data)| (interval, format!("entry {}", data)))
+                .collect();
+            assert_eq!(mapped_inorder, expected);
+
+            // The subtree maxima are preserved.
+            let matches: Vec<_> = mapped
+                .overlap_search_all(35..=35)
+                .iter()
+                .map(|entry| entry.data.as_str())
+                .collect();
+            assert_eq!(matches, ["entry 5"]);
+        }
+
+        #[test]
+        fn map_data_when_empty_works() {
+            let tree = IntervalTree::<i32, i32>::default();
+            let mapped = tree.map_data(|data| data.to_string());
+            assert_eq!(mapped.len(), 0);
+        }
+
+        #[test]
+        fn degenerate_tree_does_not_overflow_the_stack() {
+            let tree = IntervalTree::new_from_node(construct_degenerate_chain(100_000));
+            let mut next = 0;
+            let mapped = tree.map_data(|_| {
+                next += 1;
+                next
+            });
+            assert_eq!(mapped.height(), 100_000);
+            assert_eq!(
+                mapped.overlap_search(100_000..=100_000).unwrap().data,
+                100_000
+            );
+        }
+    }
+
+    mod newtype {
+        use super::*;
+        use std::ops::Sub;
+
+        /// A point in time in milliseconds. Intentionally not `Copy`.
+        #[derive(Debug, Clone, PartialEq, PartialOrd)]
+        struct Millis(i64);
+
+        impl IntervalType for Millis {}
+
+        impl Sub for Millis {
+            type Output = Millis;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                Millis(self.0 - rhs.0)
+            }
+        }
+
+        fn span(start: i64, end: i64) -> Interval<Millis> {
+            Interval::new(Millis(start), Millis(end))
+        }
+
+        #[test]
+        fn full_api_works() {
+            let mut tree = IntervalTree::from_iter([(span(0, 100), "A"), (span(150, 300), "B")]);
+            tree.insert((span(120, 130), "C"));
+            assert!(tree.insert_unique((span(400, 500), "D")));
+            assert!(!tree.insert_unique((span(400, 500), "E")));
+            assert_eq!(tree.len(), 4);
+
+            let inorder: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+            assert_eq!(inorder, ["A", "C", "B", "D"]);
+
+            assert_eq!(tree.overlap_search(span(90, 95)).unwrap().data, "A");
+            assert!(tree.overlap_search(span(101, 119)).is_none());
+            assert_eq!(tree.overlap_search_all(span(95, 155)).len(), 3);
+            assert_eq!(tree.contained_in(span(100, 300)).len(), 2);
+
+            *tree.get_mut(&span(120, 130)).unwrap() = "F";
+            assert_eq!(tree.overlap_search(span(125, 125)).unwrap().data, "F");
+
+            assert_eq!(
+                tree.first_gap(span(0, 1000), Millis(50)),
+                Some(span(300, 400))
+            );
+
+            tree.rebalance();
+            assert_eq!(tree.height(), 3);
+
+            let index = tree.into_static();
+            assert_eq!(index.stab(Millis(450))[0].data, "D");
+            assert_eq!(index.overlap_search_all(span(0, 1000)).len(), 4);
+        }
+    }
+
+    mod unbounded {
+        use super::*;
+
+        fn mixed_tree() -> IntervalTree<Endpoint<i32>, &'static str> {
+            let mut tree = IntervalTree::default();
+            tree.insert((Interval::bounded(10, 20), "A"));
+            tree.insert((30.., "B"));
+            tree.insert((..=0, "C"));
+            tree.insert((Interval::bounded(5, 8), "D"));
+            tree.insert((.., "E"));
+            tree.insert((Interval::bounded(40, 50), "F"));
+            tree
+        }
+
+        fn data_of(
+            entries: Vec<&IntervalTreeEntry<Endpoint<i32>, &'static str>>,
+        ) -> Vec<&'static str> {
+            let mut data: Vec<_> = entries.iter().map(|entry| entry.data).collect();
+            data.sort_unstable();
+            data
+        }
+
+        #[test]
+        fn inorder_works() {
+            let tree = mixed_tree();
+            let inorder: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+            assert_eq!(inorder, ["C", "E", "D", "A", "B", "F"]);
+        }
+
+        #[test]
+        fn overlap_search_all_works() {
+            let tree = mixed_tree();
+            assert_eq!(
+                data_of(tree.overlap_search_all(Interval::bounded(1000, 1000))),
+                ["B", "E"]
+            );
+            assert_eq!(
+                data_of(tree.overlap_search_all(Interval::bounded(-1000, -1000))),
+                ["C", "E"]
+            );
+            assert_eq!(
+                data_of(tree.overlap_search_all(Interval::bounded(19, 45))),
+                ["A", "B", "E", "F"]
+            );
+            assert_eq!(data_of(tree.overlap_search_all(25..)), ["B", "E", "F"]);
+            assert_eq!(data_of(tree.overlap_search_all(..=5)), ["C", "D", "E"]);
+            assert_eq!(tree.overlap_search_all(..).len(), 6);
+        }
+
+        #[test]
+        fn search_works_after_rebalance() {
+            let mut tree = mixed_tree();
+            tree.rebalance();
+            assert_eq!(
+                data_of(tree.overlap_search_all(Interval::bounded(7, 7))),
+                ["D", "E"]
+            );
+            assert_eq!(data_of(tree.overlap_search_all(1000..)), ["B", "E"]);
+
+            let index = tree.into_static();
+            let matches: Vec<_> = index
+                .stab(Endpoint::from(35))
+                .iter()
+                .map(|e| e.data)
+                .collect();
+            assert_eq!(matches, ["E", "B"]);
+        }
+    }
+
     mod multi_dimensional {
         use super::*;
 
@@ -0,0 +1,205 @@
+//! `Endpoint<T>` for capturing open-ended intervals.
+use crate::interval_tree::{Interval, IntervalType};
+use std::fmt::{Display, Formatter};
+use std::ops::{RangeFrom, RangeFull, RangeToInclusive};
+
+/// An interval boundary that is either a finite value or unbounded in either direction.
+///
+/// # Remarks
+/// Use `Endpoint<T>` as the interval type of an [`IntervalTree`](crate::IntervalTree)
+/// to store intervals without a start or an end, e.g. "from now onward". Since
+/// [`NegativeInfinity`](Endpoint::NegativeInfinity) orders before and
+/// [`PositiveInfinity`](Endpoint::PositiveInfinity) orders after every finite value,
+/// overlap tests and the subtree maxima of the tree work without special-casing.
+/// Trees over bounded interval types such as `i32` are unaffected and do not pay
+/// for the additional comparisons.
+///
+/// # Example
+/// ```rust
+/// use space_partitioning::interval_tree::{Endpoint, Interval};
+/// use space_partitioning::IntervalTree;
+///
+/// let mut tree = IntervalTree::<Endpoint<i32>, &str>::default();
+/// tree.insert((Interval::bounded(0, 10), "bounded"));
+/// tree.insert((20.., "from 20 onward"));
+/// tree.insert((..=5, "until 5"));
+///
+/// let query = Interval::bounded(1000, 1000);
+/// let matches: Vec<_> = tree.overlap_search_all(query).iter().map(|e| e.data).collect();
+/// assert_eq!(matches, ["from 20 onward"]);
+/// assert_eq!(tree.overlap_search_all(..).len(), 3);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Endpoint<T> {
+    /// Lies before every finite value; used for intervals without a start.
+    NegativeInfinity,
+    /// A finite value.
+    Finite(T),
+    /// Lies after every finite value; used for intervals without an end.
+    PositiveInfinity,
+}
+
+impl<T> IntervalType for Endpoint<T> where T: IntervalType {}
+
+impl<T> Endpoint<T> {
+    /// Determines whether this endpoint is a finite value.
+    #[inline]
+    pub fn is_finite(&self) -> bool {
+        matches!(self, Endpoint::Finite(_))
+    }
+
+    /// Returns the finite value, or `None` if this endpoint is unbounded.
+    #[inline]
+    pub fn finite(&self) -> Option<&T> {
+        match self {
+            Endpoint::Finite(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<T> for Endpoint<T> {
+    /// Constructs a finite endpoint.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::Endpoint;
+    /// assert_eq!(Endpoint::from(5), Endpoint::Finite(5));
+    /// ```
+    #[inline]
+    fn from(value: T) -> Self {
+        Endpoint::Finite(value)
+    }
+}
+
+impl<T> Display for Endpoint<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::NegativeInfinity => write!(f, "-inf"),
+            Endpoint::Finite(value) => write!(f, "{}", value),
+            Endpoint::PositiveInfinity => write!(f, "inf"),
+        }
+    }
+}
+
+impl<T> Interval<Endpoint<T>>
+where
+    T: IntervalType,
+{
+    /// Constructs an interval with finite endpoints.
+    ///
+    /// # Remarks
+    /// Unbounded intervals can be constructed from `RangeFrom<T>`, `RangeToInclusive<T>`
+    /// and `RangeFull`, e.g. `Interval::from(20..)`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::{Endpoint, Interval};
+    /// let interval = Interval::bounded(-2, 10);
+    /// assert_eq!(interval.start, Endpoint::Finite(-2));
+    /// assert_eq!(interval.end, Endpoint::Finite(10));
+    /// ```
+    pub fn bounded(start: T, end: T) -> Self {
+        Interval::new(Endpoint::Finite(start), Endpoint::Finite(end))
+    }
+}
+
+impl<T> From<RangeFrom<T>> for Interval<Endpoint<T>>
+where
+    T: IntervalType,
+{
+    /// Constructs an interval without an end from a `RangeFrom<T>`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::{Endpoint, Interval};
+    /// let interval: Interval<Endpoint<_>> = (20..).into();
+    /// assert_eq!(interval.start, Endpoint::Finite(20));
+    /// assert_eq!(interval.end, Endpoint::PositiveInfinity);
+    /// ```
+    fn from(range: RangeFrom<T>) -> Self {
+        Interval::new(Endpoint::Finite(range.start), Endpoint::PositiveInfinity)
+    }
+}
+
+impl<T> From<RangeToInclusive<T>> for Interval<Endpoint<T>>
+where
+    T: IntervalType,
+{
+    /// Constructs an interval without a start from a `RangeToInclusive<T>`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::{Endpoint, Interval};
+    /// let interval: Interval<Endpoint<_>> = (..=5).into();
+    /// assert_eq!(interval.start, Endpoint::NegativeInfinity);
+    /// assert_eq!(interval.end, Endpoint::Finite(5));
+    /// ```
+    fn from(range: RangeToInclusive<T>) -> Self {
+        Interval::new(Endpoint::NegativeInfinity, Endpoint::Finite(range.end))
+    }
+}
+
+impl<T> From<RangeFull> for Interval<Endpoint<T>>
+where
+    T: IntervalType,
+{
+    /// Constructs an interval spanning all values.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::{Endpoint, Interval};
+    /// let interval: Interval<Endpoint<i32>> = (..).into();
+    /// assert_eq!(interval.start, Endpoint::NegativeInfinity);
+    /// assert_eq!(interval.end, Endpoint::PositiveInfinity);
+    /// ```
+    fn from(_: RangeFull) -> Self {
+        Interval::new(Endpoint::NegativeInfinity, Endpoint::PositiveInfinity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ordering_works() {
+        assert!(Endpoint::NegativeInfinity < Endpoint::Finite(i32::MIN));
+        assert!(Endpoint::Finite(i32::MAX) < Endpoint::PositiveInfinity);
+        assert!(Endpoint::Finite(-1.5) < Endpoint::Finite(2.0));
+    }
+
+    #[test]
+    fn unbounded_overlaps_with_bounded_works() {
+        let onward = Interval::from(20..);
+        let until = Interval::from(..=5);
+        assert!(onward.overlaps_with(&Interval::bounded(100, 200)));
+        assert!(onward.overlaps_with(&Interval::bounded(10, 20)));
+        assert!(!onward.overlaps_with(&Interval::bounded(10, 19)));
+        assert!(until.overlaps_with(&Interval::bounded(-100, -50)));
+        assert!(until.overlaps_with(&Interval::bounded(5, 10)));
+        assert!(!until.overlaps_with(&Interval::bounded(6, 10)));
+    }
+
+    #[test]
+    fn unbounded_overlaps_with_unbounded_works() {
+        let onward = Interval::from(20..);
+        let until = Interval::from(..=5);
+        assert!(!onward.overlaps_with(&until));
+        assert!(!until.overlaps_with(&onward));
+        assert!(onward.overlaps_with(&(30..).into()));
+        assert!(until.overlaps_with(&(..=0).into()));
+        assert!(onward.overlaps_with(&(..).into()));
+        assert!(until.overlaps_with(&(..).into()));
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(format!("{}", Interval::from(20..)), "[20, inf]");
+        assert_eq!(format!("{}", Interval::from(..=5)), "[-inf, 5]");
+    }
+}
@@ -0,0 +1,17 @@
+use std::{error, fmt};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InsertError {
+    /// The start or the end of the interval that was about to be inserted was not a number.
+    NanBound,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::NanBound => write!(f, "the interval has a bound that is not a number"),
+        }
+    }
+}
+
+impl error::Error for InsertError {}
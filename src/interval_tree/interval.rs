@@ -1,10 +1,11 @@
 //! `Interval<T>` for capturing intervals.
 pub use crate::interval_tree::interval_type::IntervalType;
 use std::fmt::{Debug, Display, Formatter};
-use std::ops::RangeInclusive;
+use std::ops::{RangeInclusive, Sub};
 
 /// Structure to represent an interval.
 #[derive(Default, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interval<T>
 where
     T: IntervalType,
@@ -45,6 +46,96 @@ where
     pub fn overlaps_with(&self, other: &Interval<T>) -> bool {
         (self.start <= other.end) && (other.start <= self.end)
     }
+
+    /// Checks whether the current interval contains a point, including its boundaries.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::Interval;
+    /// let interval = Interval::from(-2.0..=10.0);
+    /// assert!(interval.contains_point(10.0));
+    /// assert!(!interval.contains_point(20.0));
+    /// ```
+    pub fn contains_point(&self, p: T) -> bool {
+        (self.start <= p) && (p <= self.end)
+    }
+
+    /// Checks whether the current interval entirely contains another one.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::Interval;
+    /// let interval = Interval::from(-2.0..=10.0);
+    /// assert!(interval.contains(&(0.0..=2.0).into()));
+    /// assert!(!interval.contains(&(5.0..=20.0).into()));
+    /// ```
+    pub fn contains(&self, other: &Interval<T>) -> bool {
+        (self.start <= other.start) && (other.end <= self.end)
+    }
+
+    /// Checks whether the start or the end of the interval is not a number.
+    ///
+    /// # Remarks
+    /// A bound is considered not a number if it does not compare to itself, which
+    /// also covers newtypes wrapping a floating-point value.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::Interval;
+    /// assert!(Interval::new(f64::NAN, 10.0).has_nan_bound());
+    /// assert!(!Interval::new(-2.0, 10.0).has_nan_bound());
+    /// ```
+    pub fn has_nan_bound(&self) -> bool {
+        Self::is_unordered(&self.start) || Self::is_unordered(&self.end)
+    }
+
+    #[inline]
+    fn is_unordered(value: &T) -> bool {
+        value.partial_cmp(value).is_none()
+    }
+}
+
+impl<T> Interval<T>
+where
+    T: IntervalType + Sub<Output = T>,
+{
+    /// Checks whether the current interval overlaps with another one, treating
+    /// intervals that are separated by no more than `eps` as overlapping.
+    ///
+    /// # Remarks
+    /// This is useful for floating-point intervals whose boundaries are subject
+    /// to rounding errors. Use [`Interval::overlaps_with`] for exact comparisons.
+    /// The tolerance must not be negative. The comparison does not overflow for
+    /// integral types, even for boundaries at the limits of the type.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::interval_tree::Interval;
+    /// let interval = Interval::from(0.0..=0.7 - 0.4);
+    /// assert!(!interval.overlaps_with(&(0.3..=1.0).into()));
+    /// assert!(interval.overlaps_with_eps(&(0.3..=1.0).into(), 1e-9));
+    /// ```
+    pub fn overlaps_with_eps(&self, other: &Interval<T>, eps: T) -> bool {
+        Self::is_at_most_eps_after(&self.start, &other.end, &eps)
+            && Self::is_at_most_eps_after(&other.start, &self.end, &eps)
+    }
+
+    /// Checks whether `start` lies no more than `eps` after `end`, i.e. `start <= end + eps`,
+    /// without overflowing at the limits of integral types.
+    fn is_at_most_eps_after(start: &T, end: &T, eps: &T) -> bool {
+        if start <= end {
+            return true;
+        }
+
+        // The distance between two values of the same sign always fits the type,
+        // while subtracting the tolerance from a positive value never underflows.
+        let zero = eps.clone() - eps.clone();
+        if *end >= zero || *start <= zero {
+            start.clone() - end.clone() <= *eps
+        } else {
+            start.clone() - eps.clone() <= *end
+        }
+    }
 }
 
 impl<T> Debug for Interval<T>
@@ -130,3 +221,58 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overlaps_with_eps_within_epsilon_works() {
+        let a = Interval::from(0.0..=1.0);
+        let b = Interval::from(1.05..=2.0);
+        assert!(!a.overlaps_with(&b));
+        assert!(a.overlaps_with_eps(&b, 0.1));
+        assert!(b.overlaps_with_eps(&a, 0.1));
+    }
+
+    #[test]
+    fn overlaps_with_eps_beyond_epsilon_works() {
+        let a = Interval::from(0.0..=1.0);
+        let b = Interval::from(1.05..=2.0);
+        assert!(!a.overlaps_with_eps(&b, 0.01));
+        assert!(!b.overlaps_with_eps(&a, 0.01));
+    }
+
+    #[test]
+    fn overlaps_with_eps_when_overlapping_works() {
+        let a = Interval::from(0.0..=1.0);
+        let b = Interval::from(0.5..=2.0);
+        assert!(a.overlaps_with_eps(&b, 0.0));
+        assert!(b.overlaps_with_eps(&a, 0.0));
+    }
+
+    #[test]
+    fn overlaps_with_eps_for_unsigned_starting_at_zero_works() {
+        let a = Interval::<u32>::new(0, 5);
+        assert!(a.overlaps_with_eps(&Interval::new(6, 9), 1));
+        assert!(Interval::new(6, 9).overlaps_with_eps(&a, 1));
+        assert!(!a.overlaps_with_eps(&Interval::new(7, 9), 1));
+        assert!(a.overlaps_with_eps(&Interval::new(0, 0), 1));
+        assert!(
+            !Interval::<u32>::new(0, 0).overlaps_with_eps(&Interval::new(u32::MAX, u32::MAX), 1)
+        );
+    }
+
+    #[test]
+    fn overlaps_with_eps_at_the_limits_of_signed_types_works() {
+        let a = Interval::new(i32::MIN, i32::MIN + 2);
+        assert!(a.overlaps_with_eps(&Interval::new(i32::MIN + 3, 0), 1));
+        assert!(!a.overlaps_with_eps(&Interval::new(i32::MIN + 4, 0), 1));
+
+        // The distance between the intervals exceeds the range of the type.
+        let b = Interval::new(i32::MAX - 1, i32::MAX);
+        assert!(!a.overlaps_with_eps(&b, 1));
+        assert!(!b.overlaps_with_eps(&a, 1));
+        assert!(b.overlaps_with_eps(&Interval::new(i32::MIN, i32::MAX - 2), 1));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::interval_tree::{Interval, IntervalType};
 use std::fmt::{Debug, Formatter};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntervalTreeEntry<T, D>
 where
     T: IntervalType,
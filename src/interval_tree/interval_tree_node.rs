@@ -64,51 +64,71 @@ where
     }
 
     /// Gets the size of the tree, i.e., the number of intervals stored.
+    ///
+    /// # Remarks
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support degenerate (i.e., very deep) trees.
     pub(crate) fn len(&self) -> usize {
-        let mut size = 1;
-        if let Some(left) = &self.left {
-            size += left.len();
-        }
-        if let Some(right) = &self.right {
-            size += right.len();
+        let mut size = 0;
+        let mut to_process = vec![self];
+        while let Some(node) = to_process.pop() {
+            size += 1;
+            to_process.extend(node.left.as_deref());
+            to_process.extend(node.right.as_deref());
         }
         size
     }
 
+    /// Gets the height of the tree, i.e., the number of nodes on the longest
+    /// path from this node to a leaf. A single node has a height of one.
+    ///
+    /// # Remarks
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support degenerate (i.e., very deep) trees.
+    pub(crate) fn height(&self) -> usize {
+        let mut height = 0;
+        let mut to_process = vec![(self, 1)];
+        while let Some((node, depth)) = to_process.pop() {
+            height = height.max(depth);
+            to_process.extend(node.left.as_deref().map(|left| (left, depth + 1)));
+            to_process.extend(node.right.as_deref().map(|right| (right, depth + 1)));
+        }
+        height
+    }
+
     /// A utility function to insert a new Interval Search Tree Node
+    ///
+    /// # Remarks
+    /// This method descends iteratively instead of recursively in order to
+    /// support degenerate (i.e., very deep) trees.
     pub(crate) fn insert(&mut self, node: IntervalTreeNode<T, D>) -> &Self {
         // This is similar to BST Insert.  Here the low value of interval
         // is used to maintain BST property
+        let high = node.entry.interval.end.clone();
 
-        // Get low/high value of interval at root.
-        let low = self.entry.interval.start.clone();
-        let high = self.entry.interval.end.clone();
+        let mut current = &mut *self;
+        loop {
+            // Update the max value of this ancestor if needed; the new interval
+            // becomes part of this node's subtree.
+            if current.max < high {
+                current.max = high.clone();
+            }
 
-        // If root's low value is smaller, then new interval goes to
-        // left subtree, otherwise it goes to the right subtree.
-        if node.entry.interval.start < low {
-            match &mut self.left {
-                Some(left) => {
-                    left.insert(node);
-                }
-                None => {
-                    self.left = Some(Box::new(node));
-                }
+            // If the current node's low value is smaller, then new interval goes to
+            // left subtree, otherwise it goes to the right subtree.
+            let child = if node.entry.interval.start < current.entry.interval.start {
+                &mut current.left
+            } else {
+                &mut current.right
             };
-        } else {
-            match &mut self.right {
-                Some(right) => {
-                    right.insert(node);
-                }
+
+            match child {
+                Some(next) => current = next,
                 None => {
-                    self.right = Some(Box::new(node));
+                    *child = Some(Box::new(node));
+                    break;
                 }
-            };
-        }
-
-        // Update the max value of this ancestor if needed
-        if self.max < high {
-            self.max = high;
+            }
         }
 
         self
@@ -120,30 +140,309 @@ where
         &self,
         interval: &Interval<T>,
     ) -> Option<&IntervalTreeEntry<T, D>> {
-        // Check for overlap with root.
-        if self.entry.interval.overlaps_with(interval) {
-            return Some(&self.entry);
+        let mut node = self;
+        loop {
+            // Check for overlap with root.
+            if node.entry.interval.overlaps_with(interval) {
+                return Some(&node.entry);
+            }
+
+            // If left child of root is present and max of left child is
+            // greater than or equal to given interval, then the interval may
+            // overlap with an interval of left subtree.
+            // Else interval can only overlap with right subtree, or not at all.
+            node = match (&node.left, &node.right) {
+                (Some(left), _) if left.max >= interval.start => left,
+                (_, Some(right)) => right,
+                _ => return None,
+            };
         }
+    }
+
+    /// Collects all entries whose intervals overlap with the given interval.
+    pub(crate) fn collect_overlapping<'a>(
+        &'a self,
+        interval: &Interval<T>,
+        out: &mut Vec<&'a IntervalTreeEntry<T, D>>,
+    ) {
+        let mut to_process = Vec::new();
+        self.collect_overlapping_with(interval, &mut to_process, out);
+    }
 
-        // If left child of root is present and max of left child is
-        // greater than or equal to given interval, then the interval may
-        // overlap with an interval of left subtree.
-        if self.left.is_some() && self.left.as_ref().unwrap().max >= interval.start {
-            return self.left.as_ref().unwrap().overlap_search(interval);
+    /// Like [`collect_overlapping()`] but uses the provided list as the traversal stack.
+    /// The list is expected to be empty and will be empty after the call.
+    pub(crate) fn collect_overlapping_with<'a>(
+        &'a self,
+        interval: &Interval<T>,
+        to_process: &mut Vec<&'a IntervalTreeNode<T, D>>,
+        out: &mut Vec<&'a IntervalTreeEntry<T, D>>,
+    ) {
+        self.for_each_overlapping_with(interval, to_process, |entry| out.push(entry));
+    }
+
+    /// Calls a function for each entry whose interval overlaps with the given interval,
+    /// using the provided list as the traversal stack.
+    /// The list is expected to be empty and will be empty after the call.
+    pub(crate) fn for_each_overlapping_with<'a, F>(
+        &'a self,
+        interval: &Interval<T>,
+        to_process: &mut Vec<&'a IntervalTreeNode<T, D>>,
+        mut f: F,
+    ) where
+        F: FnMut(&'a IntervalTreeEntry<T, D>),
+    {
+        debug_assert!(to_process.is_empty());
+        to_process.push(self);
+        while let Some(node) = to_process.pop() {
+            // No interval in this subtree ends after the query starts.
+            if node.max < interval.start {
+                continue;
+            }
+
+            if node.entry.interval.overlaps_with(interval) {
+                f(&node.entry);
+            }
+
+            to_process.extend(node.left.as_deref());
+
+            // All intervals of the right subtree start at or after this one.
+            if node.entry.interval.start <= interval.end {
+                to_process.extend(node.right.as_deref());
+            }
+        }
+    }
+
+    /// Collects all entries whose intervals are entirely contained in `range`,
+    /// in order of their interval starts.
+    ///
+    /// # Remarks
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support degenerate (i.e., very deep) trees.
+    pub(crate) fn collect_contained_in<'a>(
+        &'a self,
+        range: &Interval<T>,
+        out: &mut Vec<&'a IntervalTreeEntry<T, D>>,
+    ) {
+        let mut to_process = Vec::new();
+        let mut node = Some(self);
+        loop {
+            // All intervals of the left subtree start before this one; if this one
+            // doesn't start after the range start, none of them is contained.
+            while let Some(current) = node {
+                to_process.push(current);
+                node = if current.entry.interval.start > range.start {
+                    current.left.as_deref()
+                } else {
+                    None
+                };
+            }
+
+            let current = match to_process.pop() {
+                Some(current) => current,
+                None => break,
+            };
+
+            if range.contains(&current.entry.interval) {
+                out.push(&current.entry);
+            }
+
+            // All intervals of the right subtree start at or after this one.
+            node = if current.entry.interval.start <= range.end {
+                current.right.as_deref()
+            } else {
+                None
+            };
         }
+    }
+
+    /// Searches for the entry whose interval exactly equals the given one.
+    pub(crate) fn get(&self, interval: &Interval<T>) -> Option<&IntervalTreeEntry<T, D>> {
+        let mut node = self;
+        loop {
+            if node.entry.interval == *interval {
+                return Some(&node.entry);
+            }
+
+            // Follow the same path an insert of this interval would take.
+            let child = if interval.start < node.entry.interval.start {
+                &node.left
+            } else {
+                &node.right
+            };
 
-        // Else interval can only overlap with right subtree, or not at all.
-        if self.right.is_some() {
-            return self.right.as_ref().unwrap().overlap_search(interval);
+            node = child.as_deref()?;
         }
+    }
 
-        None
+    /// Searches for the node whose interval exactly equals the given one
+    /// and returns a mutable reference to its data.
+    ///
+    /// Since intervals with equal starts are always inserted to the right subtree,
+    /// the first match found while descending is the earliest inserted one.
+    pub(crate) fn get_mut(&mut self, interval: &Interval<T>) -> Option<&mut D> {
+        let mut node = self;
+        loop {
+            if node.entry.interval == *interval {
+                return Some(&mut node.entry.data);
+            }
+
+            // Follow the same path an insert of this interval would take.
+            let child = if interval.start < node.entry.interval.start {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+
+            node = child.as_deref_mut()?;
+        }
     }
 
     /// Iterates the tree in-order, i.e. earlier-starting intervals first.
     pub(crate) fn iter_inorder(&self) -> InorderIterator<T, D> {
         InorderIterator::new(&self)
     }
+
+    /// Builds a balanced tree from entries sorted by interval start.
+    ///
+    /// # Remarks
+    /// The median entry of each range becomes the root of its subtree. Among
+    /// entries with equal starts the earliest one is chosen, such that the left
+    /// subtree only contains intervals starting strictly before the root, just
+    /// like [`insert`](Self::insert) produces it.
+    ///
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support many intervals sharing the same start, which form a chain.
+    pub(crate) fn from_sorted(entries: Vec<IntervalTreeEntry<T, D>>) -> Option<Self> {
+        enum Task {
+            /// Builds the subtree of the entries in the range `[lo, hi)`.
+            Build(usize, usize),
+            /// Joins the entry at the index with the two subtrees built last.
+            Join(usize),
+        }
+
+        let mut entries: Vec<_> = entries.into_iter().map(Some).collect();
+        let mut tasks = vec![Task::Build(0, entries.len())];
+        let mut built: Vec<ChildNode<T, D>> = Vec::new();
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Build(lo, hi) if lo == hi => built.push(None),
+                Task::Build(lo, hi) => {
+                    let median = lo + (hi - lo) / 2;
+                    let start = entries[median].as_ref().unwrap().interval.start.clone();
+                    let mid = lo
+                        + entries[lo..median].partition_point(|entry| {
+                            entry.as_ref().unwrap().interval.start < start
+                        });
+
+                    // The left subtree is built first and is thus popped last.
+                    tasks.push(Task::Join(mid));
+                    tasks.push(Task::Build(mid + 1, hi));
+                    tasks.push(Task::Build(lo, mid));
+                }
+                Task::Join(mid) => {
+                    let right = built.pop().unwrap();
+                    let left = built.pop().unwrap();
+
+                    let mut node = Self::new(entries[mid].take().unwrap());
+                    for child in left.iter().chain(right.iter()) {
+                        if node.max < child.max {
+                            node.max = child.max.clone();
+                        }
+                    }
+
+                    node.left = left;
+                    node.right = right;
+                    built.push(Some(Box::new(node)));
+                }
+            }
+        }
+
+        debug_assert_eq!(built.len(), 1);
+        built.pop().unwrap().map(|node| *node)
+    }
+
+    /// Consumes the tree and returns a tree of the same shape whose data is transformed
+    /// by the specified function. The function is called for the entries in-order.
+    ///
+    /// # Remarks
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support degenerate (i.e., very deep) trees.
+    pub(crate) fn map_data<E, F>(self, f: &mut F) -> IntervalTreeNode<T, E>
+    where
+        F: FnMut(&D) -> E,
+    {
+        enum Task<T: IntervalType, D> {
+            /// Maps the subtree rooted at the node.
+            Visit(ChildNode<T, D>),
+            /// Maps the entry of a node whose left subtree was mapped last.
+            Map(IntervalTreeEntry<T, D>),
+            /// Joins the entry mapped last with the two subtrees mapped last.
+            Join(T),
+        }
+
+        let mut tasks = vec![Task::Visit(Some(Box::new(self)))];
+        let mut mapped: Vec<IntervalTreeEntry<T, E>> = Vec::new();
+        let mut built: Vec<ChildNode<T, E>> = Vec::new();
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(None) => built.push(None),
+                Task::Visit(Some(node)) => {
+                    let node = *node;
+
+                    // The left subtree is visited first and is thus pushed last.
+                    tasks.push(Task::Join(node.max));
+                    tasks.push(Task::Visit(node.right));
+                    tasks.push(Task::Map(node.entry));
+                    tasks.push(Task::Visit(node.left));
+                }
+                Task::Map(entry) => mapped.push(IntervalTreeEntry {
+                    data: f(&entry.data),
+                    interval: entry.interval,
+                }),
+                Task::Join(max) => {
+                    let right = built.pop().unwrap();
+                    let left = built.pop().unwrap();
+                    let entry = mapped.pop().unwrap();
+                    built.push(Some(Box::new(IntervalTreeNode {
+                        entry,
+                        max,
+                        left,
+                        right,
+                    })));
+                }
+            }
+        }
+
+        debug_assert_eq!(built.len(), 1);
+        *built.pop().unwrap().unwrap()
+    }
+
+    /// Consumes the tree and returns its entries in-order, i.e. earlier-starting intervals first.
+    ///
+    /// # Remarks
+    /// This method uses an explicit stack instead of recursion in order to
+    /// support degenerate (i.e., very deep) trees.
+    pub(crate) fn into_inorder(self) -> Vec<IntervalTreeEntry<T, D>> {
+        let mut entries = Vec::new();
+        let mut to_process = Vec::new();
+        let mut current = Some(self);
+        loop {
+            while let Some(mut node) = current {
+                current = node.left.take().map(|node| *node);
+                to_process.push(node);
+            }
+
+            match to_process.pop() {
+                Some(mut node) => {
+                    current = node.right.take().map(|node| *node);
+                    entries.push(node.entry);
+                }
+                None => return entries,
+            }
+        }
+    }
 }
 
 impl<T, D> From<IntervalTreeEntry<T, D>> for IntervalTreeNode<T, D>
@@ -204,6 +503,23 @@ pub(crate) mod test {
             .unwrap()
     }
 
+    /// Constructs a degenerate tree of `n` ascending intervals `i..=i+1`,
+    /// each of which is the right child of its predecessor.
+    ///
+    /// The chain is built bottom-up since inserting the intervals one by one
+    /// takes quadratic time.
+    pub fn construct_degenerate_chain(n: i32) -> IntervalTreeNode<i32, ()> {
+        assert!(n > 0);
+        let mut node = IntervalTreeNode::new_from_pair(n - 1..=n, ());
+        for i in (0..n - 1).rev() {
+            let mut parent = IntervalTreeNode::new_from_pair(i..=i + 1, ());
+            parent.max = node.max;
+            parent.right = Some(Box::new(node));
+            node = parent;
+        }
+        node
+    }
+
     #[test]
     fn overlap_search_works() {
         let root = construct_test_root_node();
@@ -216,4 +532,11 @@ pub(crate) mod test {
         let root = construct_test_root_node();
         assert_eq!(root.len(), 6);
     }
+
+    #[test]
+    fn height_works() {
+        let root = construct_test_root_node();
+        assert_eq!(root.height(), 3);
+        assert_eq!(IntervalTreeNode::new_from_pair(0..=1, ()).height(), 1);
+    }
 }
@@ -2,6 +2,10 @@
 
 /// A marker trait for interval types. Default implemented for standard integral and floating-point types.
 ///
+/// # Remarks
+/// Implementing the trait for a newtype, e.g. `struct Millis(i64)`, prevents
+/// mixing intervals of different units. The type does not need to be `Copy`.
+///
 /// # Example
 /// ```rust
 /// use space_partitioning::interval_tree::IntervalType;
@@ -19,9 +23,15 @@ pub trait IntervalType: Clone + PartialOrd + PartialEq {}
 impl IntervalType for i8 {}
 impl IntervalType for u8 {}
 
+impl IntervalType for i16 {}
+impl IntervalType for u16 {}
+
 impl IntervalType for i32 {}
 impl IntervalType for u32 {}
 
+impl IntervalType for i64 {}
+impl IntervalType for u64 {}
+
 impl IntervalType for usize {}
 impl IntervalType for isize {}
 
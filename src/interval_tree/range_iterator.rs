@@ -0,0 +1,65 @@
+//! Provides an `Iterator` that yields the elements whose starting points lie in a range.
+use crate::interval_tree::interval::IntervalType;
+use crate::interval_tree::interval_tree_node::IntervalTreeNode;
+use crate::interval_tree::{Interval, IntervalTreeEntry};
+
+/// Iterates the entries whose interval starts lie within a range, in order of
+/// their starts. Subtrees whose starts lie outside of the range are skipped.
+#[derive(Debug)]
+pub(crate) struct RangeIterator<'a, T, D>
+where
+    T: IntervalType,
+{
+    range: Interval<T>,
+    /// The nodes whose entry and right subtree are yet to be visited.
+    /// Every node on the stack starts at or after the start of the range.
+    stack: Vec<&'a IntervalTreeNode<T, D>>,
+}
+
+impl<'a, T, D> RangeIterator<'a, T, D>
+where
+    T: IntervalType,
+{
+    pub(crate) fn new(root: Option<&'a IntervalTreeNode<T, D>>, range: Interval<T>) -> Self {
+        let mut iter = Self {
+            range,
+            stack: Vec::new(),
+        };
+        iter.push_left(root);
+        iter
+    }
+
+    /// Descends along the left children of the specified node, pushing all nodes
+    /// in range. Nodes starting before the range are skipped along with their left
+    /// subtree, as equal starts are stored to the right.
+    fn push_left(&mut self, mut node: Option<&'a IntervalTreeNode<T, D>>) {
+        while let Some(current) = node {
+            if current.entry.interval.start < self.range.start {
+                node = current.right.as_deref();
+            } else {
+                self.stack.push(current);
+                node = current.left.as_deref();
+            }
+        }
+    }
+}
+
+impl<'a, T, D> Iterator for RangeIterator<'a, T, D>
+where
+    T: IntervalType,
+{
+    type Item = &'a IntervalTreeEntry<T, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+
+        // All remaining nodes start at or after this one.
+        if node.entry.interval.start > self.range.end {
+            self.stack.clear();
+            return None;
+        }
+
+        self.push_left(node.right.as_deref());
+        Some(&node.entry)
+    }
+}
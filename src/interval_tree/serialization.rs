@@ -0,0 +1,152 @@
+//! Serialization of `IntervalTree<T, D>` with serde, available with the `serde` feature.
+use crate::interval_tree::interval_tree_node::IntervalTreeNode;
+use crate::interval_tree::{IntervalTree, IntervalTreeEntry, IntervalType};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl<T, D> Serialize for IntervalTree<T, D>
+where
+    T: IntervalType + Serialize,
+    D: Serialize,
+{
+    /// Serializes the entries of the tree as a sequence in order of their interval starts.
+    ///
+    /// # Remarks
+    /// The shape of the tree and the maximum ends of its subtrees are not stored.
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter_inorder())
+    }
+}
+
+impl<'de, T, D> Deserialize<'de> for IntervalTree<T, D>
+where
+    T: IntervalType + Deserialize<'de>,
+    D: Deserialize<'de>,
+{
+    /// Deserializes a sequence of entries into a balanced tree.
+    ///
+    /// # Remarks
+    /// The entries do not need to be sorted; the tree is built as if by
+    /// [`IntervalTree::rebalance()`] regardless of the shape of the serialized
+    /// tree, and the maximum ends of its subtrees are recomputed.
+    ///
+    /// # Errors
+    /// Fails if an interval has a bound that is not a number, as such entries
+    /// would break the ordering of the tree.
+    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    where
+        De: Deserializer<'de>,
+    {
+        let mut entries = Vec::<IntervalTreeEntry<T, D>>::deserialize(deserializer)?;
+        if entries.iter().any(|entry| entry.interval.has_nan_bound()) {
+            return Err(De::Error::custom(
+                "the interval has a bound that is not a number",
+            ));
+        }
+
+        entries.sort_by(|a, b| {
+            a.interval
+                .start
+                .partial_cmp(&b.interval.start)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(Self {
+            root: IntervalTreeNode::from_sorted(entries),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::iter::FromIterator;
+
+    #[test]
+    fn round_trip_works() {
+        let tree =
+            IntervalTree::from_iter((0..100).map(|start| (start * 3..=start * 3 + 10, start)));
+        assert_eq!(tree.height(), 100);
+
+        let json = serde_json::to_string(&tree).expect("serialization should work");
+        let restored: IntervalTree<i32, i32> =
+            serde_json::from_str(&json).expect("deserialization should work");
+        assert_eq!(restored.len(), tree.len());
+        assert_eq!(restored.height(), 7);
+
+        for query in [0..=0, 5..=12, 150..=151, 300..=300, 308..=400, 500..=600] {
+            let data = |tree: &IntervalTree<i32, i32>| {
+                let mut data: Vec<_> = tree
+                    .overlap_search_all(query.clone())
+                    .iter()
+                    .map(|entry| entry.data)
+                    .collect();
+                data.sort_unstable();
+                data
+            };
+            assert_eq!(data(&restored), data(&tree));
+            assert_eq!(
+                restored.overlap_search(query.clone()).is_some(),
+                tree.overlap_search(query.clone()).is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn deserialize_unsorted_works() {
+        let json = r#"[
+            {"interval": {"start": 30, "end": 40}, "data": "C"},
+            {"interval": {"start": 10, "end": 30}, "data": "B"},
+            {"interval": {"start": 15, "end": 20}, "data": "A"}
+        ]"#;
+        let tree: IntervalTree<i32, &str> =
+            serde_json::from_str(json).expect("deserialization should work");
+
+        let data: Vec<_> = tree.iter_inorder().map(|entry| entry.data).collect();
+        assert_eq!(data, ["B", "A", "C"]);
+        assert_eq!(tree.overlap_search(35..=35).unwrap().data, "C");
+    }
+
+    #[test]
+    fn deserialize_rejects_nan_bounds() {
+        /// Reads `null` as `NaN`, since JSON cannot encode it.
+        #[derive(Debug, Clone, PartialEq, PartialOrd, Deserialize)]
+        #[serde(from = "Option<f64>")]
+        struct Seconds(f64);
+
+        impl IntervalType for Seconds {}
+
+        impl From<Option<f64>> for Seconds {
+            fn from(value: Option<f64>) -> Self {
+                Seconds(value.unwrap_or(f64::NAN))
+            }
+        }
+
+        let json = r#"[
+            {"interval": {"start": 10.0, "end": 30.0}, "data": "A"},
+            {"interval": {"start": null, "end": 20.0}, "data": "B"}
+        ]"#;
+        let error = serde_json::from_str::<IntervalTree<Seconds, &str>>(json).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("the interval has a bound that is not a number"));
+
+        let json = r#"[{"interval": {"start": 10.0, "end": 30.0}, "data": "A"}]"#;
+        let tree: IntervalTree<Seconds, &str> =
+            serde_json::from_str(json).expect("deserialization should work");
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn round_trip_empty_works() {
+        let tree = IntervalTree::<i32, ()>::default();
+        let json = serde_json::to_string(&tree).expect("serialization should work");
+        assert_eq!(json, "[]");
+        let restored: IntervalTree<i32, ()> =
+            serde_json::from_str(&json).expect("deserialization should work");
+        assert!(restored.is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+//! An immutable interval index optimized for queries over a static set of intervals.
+use crate::interval_tree::{Interval, IntervalTreeEntry, IntervalType};
+use std::fmt::{Debug, Formatter};
+
+/// An immutable index over a set of intervals, obtained from
+/// [`IntervalTree::into_static`](crate::IntervalTree::into_static).
+///
+/// The entries are stored in a flat array sorted by interval start. The array
+/// is treated as an implicit balanced search tree in which the middle element
+/// of every range is the parent of both halves, and an auxiliary array stores the
+/// maximum interval end of each subtree. Queries therefore run in `O(log n + k)`
+/// without chasing pointers between nodes.
+pub struct StaticIntervalIndex<T, D>
+where
+    T: IntervalType,
+{
+    /// The entries, sorted by interval start.
+    entries: Vec<IntervalTreeEntry<T, D>>,
+    /// The maximum interval end of the implicit subtree rooted at each entry.
+    max_end: Vec<T>,
+}
+
+impl<T, D> StaticIntervalIndex<T, D>
+where
+    T: IntervalType,
+{
+    /// Builds the index from entries that are sorted by interval start.
+    pub(crate) fn from_sorted(entries: Vec<IntervalTreeEntry<T, D>>) -> Self {
+        let mut max_end: Vec<T> = entries
+            .iter()
+            .map(|entry| entry.interval.end.clone())
+            .collect();
+        if !entries.is_empty() {
+            Self::propagate_max_end(&mut max_end, 0, entries.len());
+        }
+        Self { entries, max_end }
+    }
+
+    /// Calculates the maximum end of the implicit subtree spanning `[lo, hi)`
+    /// and stores it at the subtree's root, which is returned.
+    fn propagate_max_end(max_end: &mut [T], lo: usize, hi: usize) -> usize {
+        let mid = lo + (hi - lo) / 2;
+        if lo < mid {
+            let left = Self::propagate_max_end(max_end, lo, mid);
+            if max_end[left] > max_end[mid] {
+                max_end[mid] = max_end[left].clone();
+            }
+        }
+        if mid + 1 < hi {
+            let right = Self::propagate_max_end(max_end, mid + 1, hi);
+            if max_end[right] > max_end[mid] {
+                max_end[mid] = max_end[right].clone();
+            }
+        }
+        mid
+    }
+
+    /// Gets the number of intervals stored in the index.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let index = IntervalTree::from_iter([15..=20, 10..=30]).into_static();
+    /// assert_eq!(index.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Determines whether the index is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries in order of their interval starts.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, IntervalTreeEntry<T, D>> {
+        self.entries.iter()
+    }
+
+    /// Returns all entries whose intervals overlap with the specified `interval`.
+    /// The entries are returned in order of their interval starts.
+    ///
+    /// # Parameters
+    /// * `interval` - The interval to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    /// let index = tree.into_static();
+    ///
+    /// let matches: Vec<_> = index.overlap_search_all(18..=25).iter().map(|e| e.data).collect();
+    /// assert_eq!(matches, ["B", "A"]);
+    /// ```
+    pub fn overlap_search_all<I>(&self, interval: I) -> Vec<&IntervalTreeEntry<T, D>>
+    where
+        I: Into<Interval<T>>,
+    {
+        let mut result = Vec::new();
+        if !self.entries.is_empty() {
+            self.collect_overlapping(&interval.into(), 0, self.entries.len(), &mut result);
+        }
+        result
+    }
+
+    /// Returns all entries whose intervals contain the specified `point`.
+    /// The entries are returned in order of their interval starts.
+    ///
+    /// # Parameters
+    /// * `point` - The point to query for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::IntervalTree;
+    /// use std::iter::FromIterator;
+    ///
+    /// let tree = IntervalTree::from_iter([(15..=20, "A"), (10..=30, "B"), (30..=40, "C")]);
+    /// let index = tree.into_static();
+    ///
+    /// let matches: Vec<_> = index.stab(30).iter().map(|e| e.data).collect();
+    /// assert_eq!(matches, ["B", "C"]);
+    /// ```
+    pub fn stab(&self, point: T) -> Vec<&IntervalTreeEntry<T, D>> {
+        self.overlap_search_all(Interval::new(point.clone(), point))
+    }
+
+    /// Collects the overlapping entries of the implicit subtree spanning `[lo, hi)`.
+    ///
+    /// # Remarks
+    /// Since the implicit tree is balanced, the recursion depth is logarithmic
+    /// in the number of entries.
+    fn collect_overlapping<'a>(
+        &'a self,
+        interval: &Interval<T>,
+        lo: usize,
+        hi: usize,
+        result: &mut Vec<&'a IntervalTreeEntry<T, D>>,
+    ) {
+        let mid = lo + (hi - lo) / 2;
+
+        // No interval in this subtree reaches the query.
+        if self.max_end[mid] < interval.start {
+            return;
+        }
+
+        if lo < mid {
+            self.collect_overlapping(interval, lo, mid, result);
+        }
+
+        // This entry and all entries to its right start after the query.
+        let entry = &self.entries[mid];
+        if entry.interval.start > interval.end {
+            return;
+        }
+
+        if entry.interval.overlaps_with(interval) {
+            result.push(entry);
+        }
+
+        if mid + 1 < hi {
+            self.collect_overlapping(interval, mid + 1, hi, result);
+        }
+    }
+}
+
+impl<T, D> Debug for StaticIntervalIndex<T, D>
+where
+    T: Debug + IntervalType,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "len = {}", self.len())
+    }
+}
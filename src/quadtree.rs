@@ -1,7 +1,15 @@
 mod aabb;
+mod aabb_iterator;
+mod cached_query;
 mod centered_aabb;
+mod cursor;
+mod distance_entry;
+mod duplicate_policy;
+mod entry;
 mod error;
 mod free_list;
+mod insert_outcome;
+mod morton;
 mod node;
 mod node_data;
 mod node_info;
@@ -11,12 +19,28 @@ mod quad_rect;
 mod quadrants;
 mod quadtree;
 mod quadtree_element;
+mod query_stats;
+mod scaled_quadtree;
+mod translated_view;
+mod uniform_grid;
 
 pub use aabb::AABB;
+pub use aabb_iterator::AabbIterator;
+pub use cached_query::CachedQuery;
+pub use cursor::QuadTreeCursor;
+pub use duplicate_policy::DuplicatePolicy;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use error::InsertError;
+pub use insert_outcome::InsertOutcome;
+pub use morton::morton_code;
 pub use node_info::NodeInfo;
 pub use point::Point;
 pub use quad_rect::QuadRect;
 pub use quadtree::{QuadTree, QuadTreeElement};
+pub use query_stats::QueryStats;
+pub use scaled_quadtree::ScaledQuadTree;
+pub use translated_view::TranslatedView;
+pub use uniform_grid::UniformGrid;
 
 #[cfg(test)]
 mod test {
@@ -54,6 +78,39 @@ mod test {
         assert!(inserted_ids.contains(&1));
     }
 
+    #[test]
+    fn insert_reporting_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 1, 1);
+
+        // The first element fits into the empty root.
+        let outcome = tree
+            .insert_reporting(QuadTreeElement::new(1000, AABB::new(-15, -15, -12, -12)))
+            .expect("insert should work");
+        assert_eq!(outcome.splits_performed, 0);
+        assert_eq!(outcome.final_depth, 0);
+
+        // The root is full and needs to split.
+        let outcome = tree
+            .insert_reporting(QuadTreeElement::new(2000, AABB::new(12, 12, 15, 15)))
+            .expect("insert should work");
+        assert!(outcome.splits_performed > 0);
+        assert_eq!(outcome.final_depth, 1);
+
+        // The bottom-right quadrant is occupied, splitting it further.
+        let outcome = tree
+            .insert_reporting(QuadTreeElement::new(3000, AABB::new(2, 2, 4, 4)))
+            .expect("insert should work");
+        assert_eq!(outcome.splits_performed, 1);
+        assert_eq!(outcome.final_depth, 2);
+
+        // The top-right quadrant is empty.
+        let outcome = tree
+            .insert_reporting(QuadTreeElement::new(4000, AABB::new(12, -15, 15, -12)))
+            .expect("insert should work");
+        assert_eq!(outcome.splits_performed, 0);
+        assert_eq!(outcome.final_depth, 1);
+    }
+
     #[test]
     fn insert_a_lot_works() {
         let mut tree = QuadTree::new(QuadRect::new(-16, -16, 32, 32), 8, 1, 1);
@@ -73,6 +130,1180 @@ mod test {
         assert_eq!(inserted_ids.len(), count as usize);
     }
 
+    #[test]
+    fn string_ids_work() {
+        let mut tree = QuadTree::<String>::new(QuadRect::new(-16, -16, 32, 32), 8, 1, 1);
+        let player = QuadTreeElement::new(String::from("player"), AABB::new(-10, -10, -8, -8));
+        let enemy = QuadTreeElement::new(String::from("enemy"), AABB::new(5, 5, 7, 7));
+        tree.insert(player.clone()).expect("insert should work");
+        tree.insert(enemy.clone()).expect("insert should work");
+
+        assert_eq!(
+            tree.intersect_aabb(&AABB::new(-12, -12, -6, -6)),
+            ["player"]
+        );
+        assert_eq!(tree.intersect_aabb(&AABB::new(0, 0, 10, 10)), ["enemy"]);
+
+        assert!(tree.remove(&player));
+        assert!(tree.intersect_aabb(&AABB::new(-12, -12, -6, -6)).is_empty());
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn from_elements_sorted_works() {
+        let elements: Vec<_> = (0..256)
+            .map(|id| {
+                let x = (id * 37) % 64 - 32;
+                let y = (id * 11) % 64 - 32;
+                QuadTreeElement::new(id, AABB::new(x, y, x + 2, y + 2))
+            })
+            .collect();
+
+        let quad_rect = QuadRect::new(-32, -32, 64, 64);
+        let tree = QuadTree::from_elements_sorted(quad_rect, 8, 4, 1, &elements)
+            .expect("insert should work");
+
+        let mut reference = QuadTree::new(quad_rect, 8, 4, 1);
+        for element in &elements {
+            reference.insert(*element).expect("insert should work");
+        }
+
+        let query = AABB::new(-10, -5, 7, 20);
+        let mut results = tree.intersect_aabb(&query);
+        let mut expected = reference.intersect_aabb(&query);
+        results.sort_unstable();
+        expected.sort_unstable();
+        assert!(!results.is_empty());
+        assert_eq!(results, expected);
+        assert_eq!(tree.collect_ids().len(), elements.len());
+    }
+
+    #[test]
+    fn longest_leaf_chain_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 2, 1, 1);
+        assert_eq!(tree.longest_leaf_chain(), 0);
+
+        tree.insert(QuadTreeElement::new(0, AABB::new(-15, -15, -12, -12)))
+            .expect("insert should work");
+        assert_eq!(tree.longest_leaf_chain(), 1);
+
+        // All of these elements end up in the same leaf at maximum depth.
+        for id in 1..=10 {
+            tree.insert(QuadTreeElement::new(id, AABB::new(1, 1, 2, 2)))
+                .expect("insert should work");
+        }
+        assert_eq!(tree.longest_leaf_chain(), 10);
+    }
+
+    #[test]
+    fn nearest_k_to_rect_works() {
+        let tree = build_test_tree();
+        let query = AABB::new(-20, -20, -19, -19);
+
+        let nearest = tree.nearest_k_to_rect(&query, 3);
+        assert_eq!(nearest, vec![(1001, 0), (1000, 32), (5000, 392)]);
+
+        // Asking for more elements than available returns all of them.
+        let nearest = tree.nearest_k_to_rect(&query, 10);
+        assert_eq!(nearest.len(), 6);
+        assert_eq!(nearest.last(), Some(&(4000, 1152)));
+        assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        assert!(tree.nearest_k_to_rect(&query, 0).is_empty());
+    }
+
+    #[test]
+    fn nearest_occupied_leaf_works() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 64, 64), 3, 1, 1);
+        assert_eq!(tree.nearest_occupied_leaf(Point::new(40, 20)), None);
+
+        // Two elements in the top-left corner split the top-left quadrant down
+        // to the maximum depth, leaving many empty leaves behind.
+        tree.insert(QuadTreeElement::new(1, AABB::new(2, 2, 3, 3)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(2, AABB::new(4, 4, 5, 5)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(3, AABB::new(60, 60, 61, 61)))
+            .expect("insert should work");
+
+        // The point lies in the empty top-right quadrant; the empty leaves of the
+        // top-left quadrant are nearer than the occupied bottom-right quadrant.
+        assert_eq!(
+            tree.nearest_occupied_leaf(Point::new(40, 20)),
+            Some(AABB::new(32, 32, 64, 64))
+        );
+        assert_eq!(
+            tree.nearest_occupied_leaf(Point::new(10, 20)),
+            Some(AABB::new(0, 0, 8, 8))
+        );
+    }
+
+    #[test]
+    fn intersect_tree_works() {
+        let tree = build_test_tree();
+        let mut other = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 3, 1, 1);
+        let elements = [
+            QuadTreeElement::new(1, AABB::new(-16, -16, -14, -14)),
+            QuadTreeElement::new(2, AABB::new(0, 0, 6, 6)),
+            // Misses the top-right and bottom-right elements by one unit.
+            QuadTreeElement::new(3, AABB::new(14, -4, 18, 4)),
+            QuadTreeElement::new(4, AABB::new(-18, 8, -12, 20)),
+        ];
+        for element in elements {
+            other.insert(element).expect("insert should work");
+        }
+
+        let mut pairs = tree.intersect_tree(&other);
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(1000, 1), (3000, 4), (4000, 2), (5000, 2)]);
+
+        // Swapping the trees swaps the pairs.
+        let mut swapped = other.intersect_tree(&tree);
+        swapped.sort_unstable_by_key(|&(other_id, id)| (id, other_id));
+        let swapped: Vec<_> = swapped.into_iter().map(|(a, b)| (b, a)).collect();
+        assert_eq!(swapped, pairs);
+
+        assert!(tree.intersect_tree(&QuadTree::default()).is_empty());
+    }
+
+    #[test]
+    fn intersect_aabb_masked_works() {
+        const TERRAIN: u32 = 0b001;
+        const PLAYERS: u32 = 0b010;
+        const PROJECTILES: u32 = 0b100;
+
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 1, 1);
+        tree.insert_with_mask(
+            QuadTreeElement::new(1, AABB::new(-15, -15, -5, -5)),
+            TERRAIN,
+        )
+        .expect("insert should work");
+        tree.insert_with_mask(
+            QuadTreeElement::new(2, AABB::new(-12, -12, -10, -10)),
+            PLAYERS,
+        )
+        .expect("insert should work");
+        tree.insert_with_mask(
+            QuadTreeElement::new(3, AABB::new(-8, -8, -7, -7)),
+            PLAYERS | PROJECTILES,
+        )
+        .expect("insert should work");
+        tree.insert(QuadTreeElement::new(4, AABB::new(-6, -6, 6, 6)))
+            .expect("insert should work");
+        tree.insert_with_mask(QuadTreeElement::new(5, AABB::new(5, 5, 15, 15)), PLAYERS)
+            .expect("insert should work");
+
+        let query = AABB::new(-20, -20, 0, 0);
+        let sorted = |mut ids: Vec<u32>| {
+            ids.sort_unstable();
+            ids
+        };
+
+        // Elements inserted without a mask belong to all layers.
+        assert_eq!(sorted(tree.intersect_aabb_masked(&query, TERRAIN)), [1, 4]);
+        assert_eq!(
+            sorted(tree.intersect_aabb_masked(&query, PLAYERS)),
+            [2, 3, 4]
+        );
+        assert_eq!(
+            sorted(tree.intersect_aabb_masked(&query, PROJECTILES)),
+            [3, 4]
+        );
+        assert_eq!(
+            sorted(tree.intersect_aabb_masked(&query, TERRAIN | PROJECTILES)),
+            [1, 3, 4]
+        );
+        assert!(tree.intersect_aabb_masked(&query, 0).is_empty());
+        assert_eq!(
+            sorted(tree.intersect_aabb_masked(&query, u32::MAX)),
+            sorted(tree.intersect_aabb(&query))
+        );
+
+        // The masks survive restructuring the tree.
+        tree.rebuild();
+        assert_eq!(sorted(tree.intersect_aabb_masked(&query, TERRAIN)), [1, 4]);
+        let subtree = tree.subtree(&AABB::new(-20, -20, 0, 0));
+        assert_eq!(
+            sorted(subtree.intersect_aabb_masked(&query, PROJECTILES)),
+            [3, 4]
+        );
+    }
+
+    #[test]
+    fn effective_max_depth_works() {
+        let rect = QuadRect::new(0, 0, 64, 64);
+        assert_eq!(QuadTree::<u32>::new(rect, 8, 1, 1).effective_max_depth(), 6);
+        assert_eq!(QuadTree::<u32>::new(rect, 4, 1, 1).effective_max_depth(), 4);
+        assert_eq!(
+            QuadTree::<u32>::new(rect, 8, 1, 16).effective_max_depth(),
+            2
+        );
+
+        // The cell size limits the depth just like the splits do.
+        let mut tree = QuadTree::<u32>::new(rect, 8, 1, 16);
+        tree.pre_split_to_depth(8);
+        let mut max_leaf_depth = 0;
+        tree.visit_leaves(|leaf| max_leaf_depth = max_leaf_depth.max(leaf.depth()));
+        assert_eq!(max_leaf_depth, 2);
+    }
+
+    #[test]
+    fn oversized_smallest_cell_size_disables_subdivision() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 64, 64), 8, 1, 64);
+        assert_eq!(tree.effective_max_depth(), 0);
+
+        for id in 0..4 {
+            tree.insert(QuadTreeElement::new(
+                id,
+                AABB::new(id * 10, 0, id * 10 + 4, 4),
+            ))
+            .expect("insert should work");
+        }
+        assert_eq!(tree.longest_leaf_chain(), 4);
+    }
+
+    #[test]
+    fn iter_points_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 2, 1);
+        let points = [
+            Point::new(-20, -20),
+            Point::new(-3, 7),
+            Point::new(0, 0),
+            Point::new(5, -12),
+            Point::new(19, 19),
+            Point::new(20, 20),
+        ];
+        for (id, point) in points.iter().enumerate() {
+            tree.insert_point(id as u32, *point)
+                .expect("insert should work");
+        }
+
+        let mut actual: Vec<_> = tree.iter_points().collect();
+        actual.sort_unstable_by_key(|(id, _)| *id);
+        let expected: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(id, point)| (id as u32, *point))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "the element is not a point")]
+    fn iter_points_panics_on_rects_in_debug_builds() {
+        let tree = build_test_tree();
+        let _ = tree.iter_points().count();
+    }
+
+    #[test]
+    fn insert_point_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 2, 1);
+        let mut points = Vec::new();
+        for y in (-19..20).step_by(3) {
+            for x in (-19..20).step_by(3) {
+                let id = points.len() as u32;
+                tree.insert_point(id, Point::new(x, y))
+                    .expect("insert should work");
+                points.push(Point::new(x, y));
+            }
+        }
+        assert_eq!(tree.count_element_references(), points.len());
+
+        // Points on the edges of the query are included.
+        let query = AABB::new(-1, -1, 8, 8);
+        let mut results = tree.intersect_aabb(&query);
+        results.sort_unstable();
+
+        let expected: Vec<_> = (0..points.len() as u32)
+            .filter(|&id| {
+                let p = points[id as usize];
+                (-1..=8).contains(&p.x) && (-1..=8).contains(&p.y)
+            })
+            .collect();
+        assert_eq!(expected.len(), 16);
+        assert_eq!(results, expected);
+
+        // Points are removed using their degenerate bounding box.
+        assert!(tree.remove(&QuadTreeElement::new(0, AABB::from(points[0]))));
+        assert_eq!(tree.count_element_references(), points.len() - 1);
+    }
+
+    #[test]
+    fn for_each_in_aabb_mut_rect_works() {
+        let mut tree = build_test_tree();
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+        let quadrant_br = AABB::new(1, 1, 17, 17);
+
+        // Push the top-left element into the bottom-right quadrant.
+        let mut visited = Vec::new();
+        let removed = tree.for_each_in_aabb_mut_rect(&quadrant_tl, |id, rect| {
+            visited.push(id);
+            if id == 1000 {
+                *rect = AABB::new(5, 5, 15, 15);
+            }
+        });
+        visited.sort_unstable();
+        assert_eq!(visited, vec![1000, 5000]);
+        assert!(removed.is_empty());
+
+        assert_eq!(tree.intersect_aabb(&quadrant_tl), vec![5000]);
+        let mut results = tree.intersect_aabb(&quadrant_br);
+        results.sort_unstable();
+        assert_eq!(results, vec![1000, 4000, 5000]);
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn for_each_in_aabb_mut_rect_removes_elements_out_of_bounds() {
+        let mut tree = build_test_tree();
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+
+        let removed = tree.for_each_in_aabb_mut_rect(&quadrant_tl, |id, rect| {
+            if id == 1000 {
+                *rect = rect.translated(-100, 0);
+            }
+        });
+        assert_eq!(
+            removed,
+            vec![QuadTreeElement::new(1000, AABB::new(-115, -15, -105, -5))]
+        );
+
+        assert_eq!(tree.intersect_aabb(&quadrant_tl), vec![5000]);
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn take_in_aabb_works() {
+        let mut tree = build_test_tree();
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+
+        let mut taken = tree.take_in_aabb(&quadrant_tl);
+        taken.sort_unstable_by_key(|element| element.id);
+        assert_eq!(
+            taken,
+            vec![
+                QuadTreeElement::new(1000, AABB::new(-15, -15, -5, -5)),
+                QuadTreeElement::new(5000, AABB::new(-5, -5, 5, 5)),
+            ]
+        );
+
+        // The elements are gone, but all others remain.
+        assert_eq!(tree.count_element_references(), 4);
+        assert!(tree.intersect_aabb(&quadrant_tl).is_empty());
+        assert!(tree.take_in_aabb(&quadrant_tl).is_empty());
+
+        let mut remaining = tree.collect_ids();
+        remaining.sort_unstable();
+        assert_eq!(remaining, vec![1001, 2000, 3000, 4000]);
+
+        // Taken elements can be inserted again.
+        for element in taken {
+            tree.insert(element).expect("insert should work");
+        }
+        assert_eq!(tree.intersect_aabb(&quadrant_tl).len(), 2);
+    }
+
+    #[test]
+    fn remove_by_id_scan_works() {
+        let mut tree = build_test_tree();
+        let element = QuadTreeElement::new(1337, AABB::new(-15, 5, -12, 8));
+        tree.insert(element).expect("insert should work");
+        assert_eq!(tree.count_element_references(), 7);
+
+        // The caller's copy of the rect went stale, so the regular removal misses the element.
+        let stale = QuadTreeElement::new(1337, AABB::new(12, -8, 15, -5));
+        assert!(!tree.remove(&stale));
+        assert_eq!(tree.count_element_references(), 7);
+
+        assert!(tree.remove_by_id_scan(&1337));
+        assert_eq!(tree.count_element_references(), 6);
+        assert!(!tree.collect_ids().contains(&1337));
+        assert!(!tree.intersect_aabb(&element.rect).contains(&1337));
+
+        // Nothing left to remove.
+        assert!(!tree.remove_by_id_scan(&1337));
+    }
+
+    #[test]
+    fn validate_works() {
+        let mut tree = build_test_tree();
+        assert_eq!(tree.validate(), Ok(()));
+
+        assert!(tree.remove(&QuadTreeElement::new(5000, AABB::new(-5, -5, 5, 5))));
+        assert!(tree.remove(&QuadTreeElement::new(1001, AABB::new(-20, -20, -18, -18))));
+        assert_eq!(tree.validate(), Ok(()));
+
+        tree.take_in_aabb(&AABB::new(-20, -20, 20, 20));
+        assert!(tree.cleanup());
+        assert_eq!(tree.validate(), Ok(()));
+
+        tree.insert(QuadTreeElement::new(0, AABB::new(-15, -15, -12, -12)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(1, AABB::new(12, 12, 15, 15)))
+            .expect("insert should work");
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn remove_spanning_element_after_reshaping_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-32, -32, 64, 64), 4, 1, 1);
+
+        // Spans all quadrants of the root.
+        let root_spanning = QuadTreeElement::new(0, AABB::new(-4, -4, 4, 4));
+        // Lies within the top-left quadrant of the root, but spans all of its quadrants.
+        let tl_spanning = QuadTreeElement::new(1, AABB::new(-18, -18, -14, -14));
+        tree.insert(root_spanning).expect("insert should work");
+        tree.insert(tl_spanning).expect("insert should work");
+
+        // Splits the top-left quadrant, moving the second element into its "this" node.
+        let mut others = Vec::new();
+        for (i, &(x, y)) in [
+            (-30, -30),
+            (-6, -30),
+            (-30, -6),
+            (-6, -6),
+            (20, 20),
+            (-20, 20),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let element = QuadTreeElement::new(10 + i as u32, AABB::new(x, y, x + 2, y + 2));
+            tree.insert(element).expect("insert should work");
+            others.push(element);
+        }
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Reshape the tree by removing and compacting, then growing it again.
+        for element in others.drain(..4) {
+            assert!(tree.remove(&element));
+        }
+        tree.cleanup();
+        for (i, &(x, y)) in [(-31, -31), (-25, -25), (-20, -28)].iter().enumerate() {
+            let element = QuadTreeElement::new(20 + i as u32, AABB::new(x, y, x + 1, y + 1));
+            tree.insert(element).expect("insert should work");
+            others.push(element);
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        let count = tree.count_element_references();
+
+        // Removing the spanning elements leaves no orphaned references.
+        assert!(tree.remove(&tl_spanning));
+        assert!(tree.remove(&root_spanning));
+        assert_eq!(tree.count_element_references(), count - 2);
+        assert_eq!(tree.validate(), Ok(()));
+
+        let mut remaining = tree.collect_ids();
+        remaining.sort_unstable();
+        let mut expected: Vec<_> = others.iter().map(|element| element.id).collect();
+        expected.sort_unstable();
+        assert_eq!(remaining, expected);
+        assert!(!tree.remove_by_id_scan(&0));
+        assert!(!tree.remove_by_id_scan(&1));
+    }
+
+    #[test]
+    fn entry_when_vacant_works() {
+        let mut tree = build_test_tree();
+        let rect = AABB::new(-15, 5, -12, 8);
+
+        match tree.entry(1337, rect) {
+            Entry::Occupied(_) => panic!("entry should be vacant"),
+            Entry::Vacant(entry) => {
+                assert_eq!(*entry.id(), 1337);
+                assert_eq!(*entry.rect(), rect);
+                entry.insert().expect("insert should work");
+            }
+        }
+
+        assert_eq!(tree.count_element_references(), 7);
+        assert!(tree.intersect_aabb(&rect).contains(&1337));
+    }
+
+    #[test]
+    fn entry_when_occupied_works() {
+        let mut tree = build_test_tree();
+
+        // The entry exposes the rect the element is stored with, not the requested one.
+        let entry = tree.entry(5000, AABB::new(0, 0, 1, 1));
+        assert!(entry.is_occupied());
+        assert_eq!(entry.or_insert().unwrap(), AABB::new(-5, -5, 5, 5));
+        assert_eq!(tree.count_element_references(), 6);
+
+        match tree.entry(5000, AABB::new(0, 0, 1, 1)) {
+            Entry::Vacant(_) => panic!("entry should be occupied"),
+            Entry::Occupied(entry) => {
+                assert_eq!(*entry.rect(), AABB::new(-5, -5, 5, 5));
+                let removed = entry.remove();
+                assert_eq!(removed, QuadTreeElement::new(5000, AABB::new(-5, -5, 5, 5)));
+            }
+        }
+
+        assert_eq!(tree.count_element_references(), 5);
+        assert!(!tree.entry(5000, AABB::new(0, 0, 1, 1)).is_occupied());
+    }
+
+    #[test]
+    fn upsert_when_absent_inserts() {
+        let mut tree = build_test_tree();
+        let rect = AABB::new(-15, 5, -12, 8);
+
+        tree.upsert(1337, rect).expect("insert should work");
+
+        assert_eq!(tree.len(), 7);
+        assert!(tree.intersect_aabb(&rect).contains(&1337));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn upsert_when_present_relocates() {
+        let mut tree = build_test_tree();
+        let old_rect = AABB::new(-5, -5, 5, 5);
+        let new_rect = AABB::new(12, 12, 14, 14);
+
+        tree.upsert(5000, new_rect).expect("relocation should work");
+
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.references(), 6);
+        assert!(!tree
+            .intersect_aabb(&AABB::new(-1, -1, 1, 1))
+            .contains(&5000));
+        assert!(tree.intersect_aabb(&new_rect).contains(&5000));
+        assert!(!tree.remove(&QuadTreeElement::new(5000, old_rect)));
+        assert!(tree.remove(&QuadTreeElement::new(5000, new_rect)));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn upsert_when_out_of_bounds_keeps_the_tree_unchanged() {
+        let mut tree = build_test_tree();
+        let generation = tree.generation();
+
+        assert!(matches!(
+            tree.upsert(5000, AABB::new(25, 25, 30, 30)),
+            Err(InsertError::OutOfBounds)
+        ));
+        assert!(matches!(
+            tree.upsert(1337, AABB::new(-30, 0, -25, 5)),
+            Err(InsertError::OutOfBounds)
+        ));
+
+        assert_eq!(tree.generation(), generation);
+        assert_eq!(tree.len(), 6);
+        assert!(tree
+            .intersect_aabb(&AABB::new(-1, -1, 1, 1))
+            .contains(&5000));
+    }
+
+    #[test]
+    fn upsert_follows_removals_of_duplicate_ids() {
+        let mut tree = build_test_tree();
+        tree.insert(QuadTreeElement::new(7, AABB::new(-15, -15, -10, -10)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(7, AABB::new(10, 10, 12, 12)))
+            .expect("insert should work");
+
+        // The element inserted first is moved.
+        assert!(tree.upsert(7, AABB::new(-2, -2, 2, 2)).is_ok());
+        assert_eq!(
+            tree.intersect_aabb(&AABB::new(-15, -15, -10, -10)),
+            vec![1000]
+        );
+
+        // Once it is removed, the remaining element is moved.
+        assert!(tree.remove(&QuadTreeElement::new(7, AABB::new(-2, -2, 2, 2))));
+        assert!(tree.upsert(7, AABB::new(-19, 10, -17, 12)).is_ok());
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.intersect_aabb(&AABB::new(10, 10, 12, 12)), vec![4000]);
+        assert_eq!(tree.intersect_aabb(&AABB::new(-19, 10, -17, 12)), vec![7]);
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Once all elements with the ID are removed, the next upsert inserts.
+        assert!(tree.remove_by_id_scan(&7));
+        assert!(tree.upsert(7, AABB::new(1, 1, 2, 2)).is_ok());
+        tree.rebuild();
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn max_elements_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 1, 1);
+        assert_eq!(tree.max_elements(), None);
+        tree.set_max_elements(Some(3));
+
+        for id in 0..3 {
+            tree.insert(QuadTreeElement::new(
+                id,
+                AABB::new(id as i32, 0, id as i32 + 1, 1),
+            ))
+            .expect("insert should work");
+        }
+        assert_eq!(tree.len(), 3);
+
+        let result = tree.insert(QuadTreeElement::new(3, AABB::new(3, 0, 4, 1)));
+        assert!(matches!(result, Err(InsertError::CapacityExceeded)));
+        assert_eq!(tree.len(), 3);
+
+        let mut ids = tree.intersect_aabb(&AABB::new(-20, -20, 20, 20));
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1, 2]);
+
+        // Removing an element makes room for another one.
+        assert!(tree.remove(&QuadTreeElement::new(0, AABB::new(0, 0, 1, 1))));
+        tree.insert(QuadTreeElement::new(3, AABB::new(3, 0, 4, 1)))
+            .expect("insert should work");
+
+        tree.set_max_elements(None);
+        tree.insert(QuadTreeElement::new(4, AABB::new(4, 0, 5, 1)))
+            .expect("insert should work");
+        assert_eq!(tree.len(), 4);
+        assert!(!tree.is_empty());
+    }
+
+    #[test]
+    fn as_uniform_grid_works() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 3, 1024, 1);
+        tree.pre_split_to_depth(3);
+        for id in 0..500u32 {
+            let x = (id as i32 * 37) % 220;
+            let y = (id as i32 * 91) % 220;
+            let size = 1 + (id as i32 % 7) * 5;
+            tree.insert(QuadTreeElement::new(
+                id,
+                AABB::new(x, y, x + size, y + size),
+            ))
+            .expect("insert should work");
+        }
+
+        let grid = tree.as_uniform_grid().expect("the tree should be uniform");
+        assert_eq!(grid.len(), 500);
+        assert_eq!(grid.cells_per_side(), 8);
+
+        let queries = [
+            AABB::new(0, 0, 256, 256),
+            AABB::new(-100, -100, 1000, 1000),
+            AABB::new(10, 10, 60, 60),
+            AABB::new(100, 0, 140, 256),
+            AABB::new(31, 31, 33, 33),
+            AABB::new(64, 64, 64, 64),
+            AABB::new(300, 300, 400, 400),
+        ];
+        for query in queries.iter() {
+            let mut expected = tree.intersect_aabb(query);
+            let mut actual = grid.intersect_aabb(query);
+            expected.sort_unstable();
+            actual.sort_unstable();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn as_uniform_grid_of_non_uniform_tree_is_none() {
+        let tree = build_test_tree();
+        assert!(tree.as_uniform_grid().is_some());
+
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 1, 1);
+        for id in 0..3 {
+            tree.insert(QuadTreeElement::new(id, AABB::new(2, 2, 3, 3)))
+                .expect("insert should work");
+        }
+        assert!(tree.as_uniform_grid().is_none());
+    }
+
+    #[test]
+    fn cached_query_works() {
+        let mut tree = build_test_tree();
+        let mut cache = CachedQuery::new(4);
+
+        let query = |x: i32, y: i32| AABB::new(x - 6, y - 6, x + 6, y + 6);
+        let expect = |tree: &QuadTree, cache: &mut CachedQuery, rect: &AABB| {
+            let mut actual = cache.intersect_aabb(tree, rect);
+            let mut expected = tree.intersect_aabb(rect);
+            actual.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(actual, expected);
+        };
+
+        expect(&tree, &mut cache, &query(-10, -10));
+        assert_eq!(cache.traversals(), 1);
+
+        // Queries within the margin are answered from the cache.
+        for (x, y) in [(-12, -10), (-8, -7), (-14, -14), (-6, -6)] {
+            expect(&tree, &mut cache, &query(x, y));
+        }
+        assert_eq!(cache.traversals(), 1);
+
+        // Leaving the cached region traverses the tree.
+        expect(&tree, &mut cache, &query(-5, -10));
+        assert_eq!(cache.traversals(), 2);
+
+        // Modifying the tree invalidates the cache.
+        tree.insert(QuadTreeElement::new(6000, AABB::new(-6, -12, -4, -10)))
+            .expect("insert should work");
+        expect(&tree, &mut cache, &query(-5, -10));
+        assert_eq!(cache.traversals(), 3);
+
+        cache.invalidate();
+        expect(&tree, &mut cache, &query(-5, -10));
+        assert_eq!(cache.traversals(), 4);
+
+        // Invalidating the cache allows querying a different tree.
+        let mut other = QuadTree::new(QuadRect::new(-32, -32, 64, 64), 4, 4, 1);
+        other
+            .insert(QuadTreeElement::new(7000, AABB::new(-6, -12, -4, -10)))
+            .expect("insert should work");
+        cache.invalidate();
+        expect(&other, &mut cache, &query(-5, -10));
+        assert_eq!(cache.traversals(), 5);
+    }
+
+    #[test]
+    fn elements_bounds_works() {
+        let mut tree = build_test_tree();
+        assert_eq!(tree.elements_bounds(), Some(AABB::new(-20, -20, 15, 15)));
+
+        assert!(tree.remove(&QuadTreeElement::new(1001, AABB::new(-20, -20, -18, -18))));
+        assert_eq!(tree.elements_bounds(), Some(AABB::new(-15, -15, 15, 15)));
+
+        let empty = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 4, 1);
+        assert_eq!(empty.elements_bounds(), None);
+    }
+
+    #[test]
+    fn topmost_at_point_works() {
+        let windows = [
+            (1, AABB::new(0, 0, 100, 100), 0),
+            (2, AABB::new(20, 20, 60, 60), 5),
+            (3, AABB::new(40, 40, 80, 80), 2),
+            (4, AABB::new(45, 45, 50, 50), 1),
+        ];
+
+        // The result does not depend on the insertion order.
+        for order in [[0, 1, 2, 3], [3, 2, 1, 0], [2, 0, 3, 1]] {
+            let mut tree = QuadTree::new(QuadRect::new(0, 0, 128, 128), 4, 1, 1);
+            for &index in &order {
+                let (id, rect, z) = windows[index];
+                tree.insert_with_z(QuadTreeElement::new(id, rect), z)
+                    .expect("insert should work");
+            }
+
+            assert_eq!(tree.topmost_at_point(Point::new(10, 10)), Some(1));
+            assert_eq!(tree.topmost_at_point(Point::new(30, 30)), Some(2));
+            assert_eq!(tree.topmost_at_point(Point::new(47, 47)), Some(2));
+            assert_eq!(tree.topmost_at_point(Point::new(70, 70)), Some(3));
+            assert_eq!(tree.topmost_at_point(Point::new(60, 60)), Some(2));
+            assert_eq!(tree.topmost_at_point(Point::new(110, 110)), None);
+        }
+    }
+
+    #[test]
+    fn topmost_at_point_prefers_the_last_inserted_element() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 128, 128), 4, 1, 1);
+        tree.insert(QuadTreeElement::new(1, AABB::new(0, 0, 50, 50)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(2, AABB::new(10, 10, 40, 40)))
+            .expect("insert should work");
+        assert_eq!(tree.topmost_at_point(Point::new(20, 20)), Some(2));
+
+        // The z-order survives rebuilding the tree.
+        tree.insert_with_z(QuadTreeElement::new(3, AABB::new(15, 15, 100, 100)), -1)
+            .expect("insert should work");
+        tree.rebuild();
+        assert_eq!(tree.topmost_at_point(Point::new(20, 20)), Some(2));
+        assert_eq!(tree.topmost_at_point(Point::new(60, 60)), Some(3));
+    }
+
+    #[test]
+    fn remove_reporting_works() {
+        let mut tree = build_test_tree();
+
+        // The center element spans all quadrants and is stored with the root's box.
+        let center = QuadTreeElement::new(5000, AABB::new(-5, -5, 5, 5));
+        let cells = tree
+            .remove_reporting(&center)
+            .expect("element should be removed");
+        assert_eq!(cells.as_slice(), [AABB::new(-20, -20, 20, 20)]);
+        assert!(tree.remove_reporting(&center).is_none());
+
+        let top_right = QuadTreeElement::new(2000, AABB::new(5, -15, 15, -5));
+        let cells = tree
+            .remove_reporting(&top_right)
+            .expect("element should be removed");
+        assert_eq!(cells.as_slice(), [AABB::new(0, -20, 20, 0)]);
+
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn explain_match_works() {
+        let tree = build_test_tree();
+
+        // The center element spans all quadrants and is stored with the root's box.
+        let query = AABB::new(-2, -2, 2, 2);
+        assert_eq!(
+            tree.explain_match(&5000, &query),
+            [AABB::new(-20, -20, 20, 20)]
+        );
+
+        let query = AABB::new(-20, -20, 20, 20);
+        assert_eq!(
+            tree.explain_match(&2000, &query),
+            [AABB::new(0, -20, 20, 0)]
+        );
+
+        // The query does not match the top-right element.
+        assert!(tree
+            .explain_match(&2000, &AABB::new(-15, 5, -5, 15))
+            .is_empty());
+        assert!(tree.explain_match(&42, &query).is_empty());
+    }
+
+    #[test]
+    fn leaves_with_elements_works() {
+        let tree = build_test_tree();
+        let leaves = tree.leaves_with_elements();
+
+        let num_ids: usize = leaves.iter().map(|(_, ids)| ids.len()).sum();
+        assert_eq!(num_ids, tree.references());
+        assert!(leaves.iter().all(|(_, ids)| !ids.is_empty()));
+
+        // The center element spans all quadrants and is stored with the root's box.
+        let (aabb, _) = leaves
+            .iter()
+            .find(|(_, ids)| ids.contains(&5000))
+            .expect("center element should be stored");
+        assert_eq!(*aabb, AABB::new(-20, -20, 20, 20));
+
+        let (aabb, ids) = leaves
+            .iter()
+            .find(|(_, ids)| ids.contains(&2000))
+            .expect("top-right element should be stored");
+        assert_eq!(*aabb, AABB::new(0, -20, 20, 0));
+        assert_eq!(ids, &vec![2000]);
+    }
+
+    #[test]
+    fn subtree_works() {
+        let tree = build_test_tree();
+
+        // The region extends beyond the tree and is clamped.
+        let region = AABB::new(-30, -30, 0, 0);
+        let subtree = tree.subtree(&region);
+
+        let mut expected = tree.intersect_aabb(&region);
+        let mut ids = subtree.collect_ids();
+        expected.sort_unstable();
+        ids.sort_unstable();
+        assert_eq!(ids, expected);
+        assert_eq!(ids, vec![1000, 1001, 5000]);
+        assert_eq!(subtree.len(), 3);
+        assert_eq!(subtree.validate(), Ok(()));
+
+        // Straddling elements retain their full bounding box.
+        assert_eq!(subtree.intersect_aabb(&AABB::new(4, 4, 5, 5)), vec![5000]);
+
+        // Regions outside of the tree result in an empty tree.
+        assert!(tree.subtree(&AABB::new(30, 30, 40, 40)).is_empty());
+    }
+
+    #[test]
+    fn subtree_keeps_straddling_elements() {
+        let mut tree = QuadTree::new(QuadRect::new(-64, -64, 128, 128), 4, 4, 1);
+        let element = QuadTreeElement::new(1, AABB::new(-20, -20, 4, 4));
+        tree.insert(element).expect("insert should work");
+
+        // The center of the element lies outside of the region.
+        let mut subtree = tree.subtree(&AABB::new(0, 0, 32, 32));
+        assert_eq!(subtree.validate(), Ok(()));
+
+        // Unchanged elements are not evicted.
+        assert!(subtree
+            .for_each_in_aabb_mut_rect(&AABB::new(0, 0, 32, 32), |_, _| {})
+            .is_empty());
+        assert_eq!(subtree.collect_ids(), vec![1]);
+
+        // Straddling elements can be moved within the subtree.
+        assert!(subtree.upsert(1, AABB::new(-18, -18, 6, 6)).is_ok());
+        assert_eq!(subtree.len(), 1);
+        assert_eq!(
+            subtree.intersect_aabb(&AABB::new(-18, -18, -17, -17)),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn generation_advances_on_mutation() {
+        let mut tree = build_test_tree();
+        let generation = tree.generation();
+
+        // Queries and no-op mutations keep the generation.
+        tree.intersect_aabb(&AABB::new(-20, -20, 20, 20));
+        assert!(!tree.remove(&QuadTreeElement::new(42, AABB::new(1, 1, 2, 2))));
+        assert!(!tree.remove_by_id_scan(&42));
+        assert!(tree.take_in_aabb(&AABB::new(16, 16, 17, 17)).is_empty());
+        assert!(!tree.cleanup());
+        assert_eq!(tree.generation(), generation);
+
+        let element = QuadTreeElement::new(42, AABB::new(1, 1, 2, 2));
+        tree.insert(element).expect("insert should work");
+        let inserted = tree.generation();
+        assert!(inserted > generation);
+
+        tree.for_each_in_aabb_mut_rect(&AABB::new(1, 1, 2, 2), |_, _| {});
+        let updated = tree.generation();
+        assert!(updated > inserted);
+
+        assert!(tree.remove(&element));
+        let removed = tree.generation();
+        assert!(removed > updated);
+
+        for id in [1000, 1001, 2000, 3000, 4000] {
+            assert!(tree.remove_by_id_scan(&id));
+        }
+        let generation = tree.generation();
+        assert!(tree.cleanup());
+        assert!(tree.generation() > generation);
+    }
+
+    #[test]
+    fn intersect_aabb_instrumented_works() {
+        let tree = build_test_tree();
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+
+        let (mut results, stats) = tree.intersect_aabb_instrumented(&quadrant_tl);
+        results.sort_unstable();
+        assert_eq!(results, vec![1000, 5000]);
+
+        // The top-left leaf and the "this" node holding the center element are
+        // visited; the element in the far corner of the leaf is tested, but missed.
+        assert_eq!(
+            stats,
+            QueryStats {
+                leaves_visited: 2,
+                elements_tested: 3,
+                candidates_matched: 2
+            }
+        );
+    }
+
+    #[test]
+    fn intersect_aabb_with_rejection_rate_works() {
+        let tree = build_test_tree();
+
+        // Every tested element matches the query covering the entire tree.
+        let (results, rate) = tree.intersect_aabb_with_rejection_rate(&AABB::new(-20, -20, 20, 20));
+        assert_eq!(results.len(), 6);
+        assert_eq!(rate, 0.0);
+
+        // One out of three tested elements misses the query.
+        let (mut results, rate) =
+            tree.intersect_aabb_with_rejection_rate(&AABB::new(-17, -17, 0, 0));
+        results.sort_unstable();
+        assert_eq!(results, [1000, 5000]);
+        assert!(rate > 0.0 && rate < 1.0);
+        assert!((rate - 1.0 / 3.0).abs() < 1e-6);
+
+        // Nothing is tested in an empty tree.
+        let (results, rate) = QuadTree::<u32>::default()
+            .intersect_aabb_with_rejection_rate(&AABB::new(-17, -17, 0, 0));
+        assert!(results.is_empty());
+        assert_eq!(rate, 0.0);
+    }
+
+    #[test]
+    fn cursor_works() {
+        let tree = build_test_tree();
+        let root = tree.cursor();
+        assert_eq!(root.aabb(), AABB::new(-20, -20, 20, 20));
+        assert_eq!(root.depth(), 0);
+        assert!(!root.is_leaf());
+        assert_eq!(root.element_count(), 0);
+        assert_eq!(root.elements().count(), 0);
+
+        let children = root.children();
+        assert_eq!(children.len(), 5);
+
+        // The first child holds the center element spanning all quadrants.
+        let spanning = &children[0];
+        assert_eq!(spanning.aabb(), root.aabb());
+        assert_eq!(spanning.depth(), 0);
+        assert_eq!(
+            spanning.elements().map(|(id, _)| id).collect::<Vec<_>>(),
+            [5000]
+        );
+
+        // Walk to the top-left leaf.
+        let top_left = &children[1];
+        assert_eq!(top_left.aabb(), AABB::new(-20, -20, 0, 0));
+        assert_eq!(top_left.depth(), 1);
+        assert!(top_left.is_leaf());
+        assert!(top_left.children().is_empty());
+        assert_eq!(top_left.element_count(), 2);
+
+        let mut elements: Vec<_> = top_left.elements().collect();
+        elements.sort_unstable_by_key(|(id, _)| *id);
+        assert_eq!(
+            elements,
+            [
+                (1000, AABB::new(-15, -15, -5, -5)),
+                (1001, AABB::new(-20, -20, -18, -18))
+            ]
+        );
+
+        let bottom_right = &children[4];
+        assert_eq!(bottom_right.aabb(), AABB::new(0, 0, 20, 20));
+        assert_eq!(
+            bottom_right
+                .elements()
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>(),
+            [4000]
+        );
+    }
+
+    #[test]
+    fn intersect_aabb_vec_works() {
+        let tree = build_test_tree();
+        let all = AABB::new(-20, -20, 20, 20);
+        assert_eq!(
+            tree.intersect_aabb_vec(&all),
+            vec![1000, 1001, 2000, 3000, 4000, 5000]
+        );
+        assert_eq!(
+            tree.intersect_aabb_vec(&AABB::new(-17, -17, 0, 0)),
+            vec![1000, 5000]
+        );
+
+        // A tree built the same way reports the same order.
+        assert_eq!(
+            build_test_tree().intersect_aabb_vec(&all),
+            tree.intersect_aabb_vec(&all)
+        );
+    }
+
+    #[test]
+    fn intersect_aabb_bitset_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-64, -64, 128, 128), 6, 2, 1);
+        for id in 0..256 {
+            let x = (id * 37) % 120 - 60;
+            let y = (id * 91) % 120 - 60;
+            tree.insert(QuadTreeElement::new(id, AABB::new(x, y, x + 3, y + 3)))
+                .expect("insert should work");
+        }
+        for id in (0..256).step_by(3) {
+            let x = (id * 37) % 120 - 60;
+            let y = (id * 91) % 120 - 60;
+            assert!(tree.remove(&QuadTreeElement::new(id, AABB::new(x, y, x + 3, y + 3))));
+        }
+
+        let a = AABB::new(-40, -40, 20, 20);
+        let b = AABB::new(-10, -30, 50, 30);
+
+        let mut bits = tree.intersect_aabb_bitset(&a);
+        bits.intersect_with(&tree.intersect_aabb_bitset(&b));
+        let mut from_bits = tree.ids_from_bitset(&bits);
+        from_bits.sort_unstable();
+
+        let in_a: std::collections::HashSet<_> = tree.intersect_aabb(&a).into_iter().collect();
+        let in_b: std::collections::HashSet<_> = tree.intersect_aabb(&b).into_iter().collect();
+        let mut expected: Vec<_> = in_a.intersection(&in_b).copied().collect();
+        expected.sort_unstable();
+
+        assert!(!expected.is_empty());
+        assert_eq!(from_bits, expected);
+    }
+
+    #[test]
+    fn intersect_aabb_vec_reflects_reinsertion() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 1, 1, 1);
+        for id in (0..8).rev() {
+            let offset = id as i32 * 2 - 8;
+            tree.insert(QuadTreeElement::new(
+                id,
+                AABB::new(offset, offset, offset + 1, offset + 1),
+            ))
+            .expect("insert should work");
+        }
+
+        let all = AABB::new(-20, -20, 20, 20);
+        assert_eq!(tree.intersect_aabb_vec(&all), vec![7, 6, 5, 4, 3, 2, 1, 0]);
+
+        // Removed and inserted again, an element moves to the end.
+        let element = QuadTreeElement::new(5, AABB::new(2, 2, 3, 3));
+        assert!(tree.remove(&element));
+        tree.insert(element).expect("insert should work");
+        assert_eq!(tree.intersect_aabb_vec(&all), vec![7, 6, 4, 3, 2, 1, 0, 5]);
+
+        // The order of the elements is kept in a subtree.
+        let subtree = tree.subtree(&all);
+        assert_eq!(
+            subtree.intersect_aabb_vec(&all),
+            vec![7, 6, 4, 3, 2, 1, 0, 5]
+        );
+    }
+
+    #[test]
+    fn iter_in_aabb_works() {
+        let tree = build_test_tree();
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+
+        let mut results: Vec<_> = tree.iter_in_aabb(&quadrant_tl).collect();
+        results.sort_unstable_by_key(|(id, _)| *id);
+        assert_eq!(
+            results,
+            vec![
+                (1000, AABB::new(-15, -15, -5, -5)),
+                (5000, AABB::new(-5, -5, 5, 5))
+            ]
+        );
+
+        // The iterator yields the same elements as the eager query.
+        for rect in [
+            AABB::new(-20, -20, 20, 20),
+            AABB::new(-17, -17, 0, 0),
+            AABB::new(1, 1, 17, 17),
+            AABB::new(-1, -1, 1, 1),
+            AABB::new(100, 100, 200, 200),
+        ] {
+            let mut expected = tree.intersect_aabb(&rect);
+            expected.sort_unstable();
+            let mut actual: Vec<_> = tree.iter_in_aabb(&rect).map(|(id, _)| id).collect();
+            actual.sort_unstable();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn iter_in_aabb_when_empty_works() {
+        let tree = QuadTree::<u32>::default();
+        assert_eq!(tree.iter_in_aabb(&AABB::new(-1, -1, 1, 1)).count(), 0);
+    }
+
+    #[test]
+    fn with_offset_works() {
+        let tree = build_test_tree();
+
+        // A view whose origin lies in the top-left corner of the tree.
+        let view = tree.with_offset(-20, -20);
+        assert_eq!(
+            view.to_tree(&AABB::new(3, 3, 20, 20)),
+            AABB::new(-17, -17, 0, 0)
+        );
+
+        let mut results = view.intersect_aabb(&AABB::new(3, 3, 20, 20));
+        results.sort_unstable();
+        assert_eq!(results, vec![1000, 5000]);
+
+        let mut count = 0;
+        view.intersect_aabb_fn(&AABB::new(3, 3, 20, 20), |_| count += 1);
+        assert_eq!(count, 2);
+    }
+
     #[test]
     fn intersect_aabb_works() {
         let tree = build_test_tree();
@@ -88,6 +1319,75 @@ mod test {
         assert!(results.contains(&5000));
     }
 
+    #[test]
+    fn intersect_aabbs_works() {
+        let tree = build_test_tree();
+
+        // Two overlapping queries that both cover the center element.
+        let quadrant_tl = AABB::new(-17, -17, 0, 0);
+        let quadrant_tr = AABB::new(-1, -17, 17, 0);
+
+        let results = tree.intersect_aabbs(&[quadrant_tl, quadrant_tr]);
+        assert_eq!(results.len(), 3);
+        assert!(results.contains(&1000));
+        assert!(results.contains(&2000));
+        assert!(results.contains(&5000));
+        assert!(!results.contains(&1001));
+        assert!(!results.contains(&3000));
+        assert!(!results.contains(&4000));
+    }
+
+    #[test]
+    fn intersect_aabb_ordered_works() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 1, 1, 1);
+        tree.insert(QuadTreeElement::new(1000, AABB::new(-15, -15, -5, -5)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(2000, AABB::new(5, -15, 15, -5)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(3000, AABB::new(-15, 5, -5, 15)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(4000, AABB::new(5, 5, 15, 15)))
+            .expect("insert should work");
+
+        let everything = AABB::new(-20, -20, 20, 20);
+
+        let mut results = Vec::new();
+        tree.intersect_aabb_ordered(&everything, Point::new(18, 18), |id| results.push(id));
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], 4000);
+        assert_eq!(results[3], 1000);
+
+        let mut results = Vec::new();
+        tree.intersect_aabb_ordered(&everything, Point::new(-18, 19), |id| results.push(id));
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], 3000);
+        assert_eq!(results[3], 2000);
+    }
+
+    #[test]
+    fn intersect_aabb_small_works() {
+        let tree = build_test_tree();
+
+        // Queries within a single leaf, as well as across leaves.
+        for query in [
+            AABB::new(-19, -19, -17, -17),
+            AABB::new(-12, -12, -10, -10),
+            AABB::new(10, 10, 12, 12),
+            AABB::new(-17, -17, 0, 0),
+            AABB::new(-20, -20, 20, 20),
+        ] {
+            let mut expected = tree.intersect_aabb(&query);
+            let mut results = tree.intersect_aabb_small(&query);
+            expected.sort_unstable();
+            results.sort_unstable();
+            assert_eq!(results.as_slice(), expected.as_slice());
+        }
+
+        let results = tree.intersect_aabb_small(&AABB::new(-19, -19, -17, -17));
+        assert_eq!(results.as_slice(), [1001]);
+        assert!(!results.spilled());
+    }
+
     #[test]
     fn intersect_generic_works() {
         let tree = build_test_tree();
@@ -131,67 +1431,7 @@ mod test {
 
     mod ray_box {
         use super::*;
-        use crate::intersections::IntersectsWith;
-
-        struct Ray {
-            x: f32,
-            y: f32,
-            inv_dx: f32,
-            inv_dy: f32,
-        }
-
-        impl Ray {
-            fn new(x: f32, y: f32, dx: f32, dy: f32) -> Ray {
-                Ray {
-                    x,
-                    y,
-                    inv_dx: 1.0 / dx,
-                    inv_dy: 1.0 / dy,
-                }
-            }
-        }
-
-        impl IntersectsWith<AABB> for Ray {
-            fn intersects_with(&self, other: &AABB) -> bool {
-                // https://gamedev.stackexchange.com/a/18459/10433
-
-                let t1 = (other.tl.x as f32 - self.x) * self.inv_dx;
-                let t2 = (other.br.x as f32 - self.x) * self.inv_dx;
-                let t3 = (other.br.y as f32 - self.y) * self.inv_dy;
-                let t4 = (other.tl.y as f32 - self.y) * self.inv_dy;
-
-                let tmin = t1.min(t2).max(t3.min(t4));
-                let tmax = t1.max(t2).min(t3.max(t4));
-
-                // if tmax < 0, ray (line) is intersecting AABB, but the whole AABB is behind us
-                // if tmin > tmax, ray doesn't intersect AABB
-                if (tmax < 0.) | (tmin > tmax) {
-                    return false;
-                }
-
-                return true;
-            }
-        }
-
-        #[test]
-        fn ray_box_intersection_works() {
-            let r#box = AABB::new(-5, -5, 5, 5);
-            let ray_in_box_pointing_up = Ray::new(0., 0., 0., 1.);
-            let ray_on_left_pointing_right = Ray::new(-10., 0., 1., 0.);
-            let ray_on_right_pointing_right = Ray::new(10., 0., 1., 0.);
-            let ray_on_right_pointing_left = Ray::new(10., 0., -1., 0.);
-            let ray_on_top_pointing_right = Ray::new(10., -10., 1., 0.);
-            let ray_on_bottom_pointing_right = Ray::new(10., 10., 1., 0.);
-            assert!(ray_in_box_pointing_up.intersects_with(&r#box));
-            assert!(ray_on_left_pointing_right.intersects_with(&r#box));
-            assert!(ray_on_right_pointing_left.intersects_with(&r#box));
-            assert!(!ray_on_right_pointing_right.intersects_with(&r#box));
-            assert!(!ray_on_top_pointing_right.intersects_with(&r#box));
-            assert!(!ray_on_bottom_pointing_right.intersects_with(&r#box));
-
-            let diagonal_ray = Ray::new(-10., 0., 1., 0.1);
-            assert!(diagonal_ray.intersects_with(&r#box));
-        }
+        use crate::intersections::Ray;
 
         #[test]
         fn tree_ray_between_elements_does_not_intersect() {
@@ -209,5 +1449,33 @@ mod test {
             assert_eq!(results.len(), 1);
             assert!(results.contains(&4000));
         }
+
+        #[test]
+        fn raycast_all_works() {
+            let mut tree = QuadTree::new(QuadRect::new(-32, -32, 64, 64), 3, 1, 1);
+            for (id, x) in [(1, 20), (2, -10), (3, 5), (4, -25)] {
+                tree.insert(QuadTreeElement::new(id, AABB::new(x, 0, x + 2, 2)))
+                    .expect("insert should work");
+            }
+
+            // Hits are ordered by distance; the box behind the origin is excluded.
+            let ray = Ray::new(-20., 1., 1., 0.);
+            assert_eq!(tree.raycast_all(&ray), vec![(2, 10.), (3, 25.), (1, 40.)]);
+
+            // Hits are ordered by distance in the opposite direction as well.
+            let ray = Ray::new(30., 1., -2., 0.);
+            assert_eq!(
+                tree.raycast_all(&ray),
+                vec![(1, 4.), (3, 11.5), (2, 19.), (4, 26.5)]
+            );
+
+            // A box containing the origin is hit immediately.
+            let ray = Ray::new(6., 1., 1., 0.);
+            assert_eq!(tree.raycast_all(&ray), vec![(3, 0.), (1, 14.)]);
+
+            // A ray missing all boxes hits nothing.
+            let ray = Ray::new(-20., 10., 1., 0.);
+            assert!(tree.raycast_all(&ray).is_empty());
+        }
     }
 }
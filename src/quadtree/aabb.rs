@@ -1,4 +1,4 @@
-use crate::intersections::IntersectsWith;
+use crate::intersections::{DistanceTo, IntersectsWith};
 use crate::quadtree::Point;
 use std::ops::{Add, RangeInclusive};
 
@@ -34,6 +34,102 @@ impl AABB {
             br: Point::new(*x.end(), *y.end()),
         }
     }
+
+    /// Constructs the smallest [`AABB`] covering the specified floating-point box
+    /// by rounding the minimum corner down and the maximum corner up.
+    ///
+    /// # Remarks
+    /// Coordinates outside the range of `i32` are clamped to its bounds;
+    /// `NaN` coordinates are treated as zero.
+    ///
+    /// # Arguments
+    /// * [`min`] - The top-left corner of the box.
+    /// * [`max`] - The bottom-right corner of the box.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::quadtree::AABB;
+    /// let aabb = AABB::from_f32_conservative([-1.5, 0.25], [2.0, 3.75]);
+    /// assert_eq!(aabb, AABB::new(-2, 0, 2, 4));
+    /// ```
+    #[inline]
+    pub fn from_f32_conservative(min: [f32; 2], max: [f32; 2]) -> Self {
+        // Float-to-integer casts saturate at the bounds of the target type.
+        Self::new(
+            min[0].floor() as i32,
+            min[1].floor() as i32,
+            max[0].ceil() as i32,
+            max[1].ceil() as i32,
+        )
+    }
+
+    /// Like [`from_f32_conservative()`], but for double precision coordinates.
+    ///
+    /// # Arguments
+    /// * [`min`] - The top-left corner of the box.
+    /// * [`max`] - The bottom-right corner of the box.
+    #[inline]
+    pub fn from_f64_conservative(min: [f64; 2], max: [f64; 2]) -> Self {
+        Self::new(
+            min[0].floor() as i32,
+            min[1].floor() as i32,
+            max[0].ceil() as i32,
+            max[1].ceil() as i32,
+        )
+    }
+
+    /// Returns a copy of this box moved by the specified offset. Coordinates
+    /// saturate at the bounds of `i32` instead of overflowing.
+    ///
+    /// # Arguments
+    /// * [`dx`] - The offset along the X axis.
+    /// * [`dy`] - The offset along the Y axis.
+    #[inline]
+    pub fn translated(&self, dx: i32, dy: i32) -> Self {
+        Self::new(
+            self.tl.x.saturating_add(dx),
+            self.tl.y.saturating_add(dy),
+            self.br.x.saturating_add(dx),
+            self.br.y.saturating_add(dy),
+        )
+    }
+
+    /// Returns a copy of this box mirrored at the X axis, i.e. with the direction
+    /// of the Y axis inverted. This converts between y-down (screen) and y-up (world)
+    /// coordinates. Coordinates saturate at the bounds of `i32` instead of overflowing.
+    #[inline]
+    pub fn flipped_y(&self) -> Self {
+        Self::new(
+            self.tl.x,
+            self.br.y.saturating_neg(),
+            self.br.x,
+            self.tl.y.saturating_neg(),
+        )
+    }
+
+    /// Calculates the squared distance of a point to this box.
+    /// Points inside the box or on its edges have a distance of zero.
+    #[inline]
+    pub(crate) fn squared_distance_to_point(&self, point: &Point) -> u64 {
+        let (x, y) = (point.x as i64, point.y as i64);
+        let dx = (self.tl.x as i64 - x).max(x - self.br.x as i64).max(0) as u64;
+        let dy = (self.tl.y as i64 - y).max(y - self.br.y as i64).max(0) as u64;
+        (dx * dx).saturating_add(dy * dy)
+    }
+
+    /// Calculates the squared distance between the closest points of this and another box.
+    /// Intersecting or touching boxes have a distance of zero. Distances exceeding
+    /// the range of `u64` saturate.
+    #[inline]
+    pub(crate) fn squared_distance_to(&self, other: &AABB) -> u64 {
+        let dx = (self.tl.x as i64 - other.br.x as i64)
+            .max(other.tl.x as i64 - self.br.x as i64)
+            .max(0) as u64;
+        let dy = (self.tl.y as i64 - other.br.y as i64)
+            .max(other.tl.y as i64 - self.br.y as i64)
+            .max(0) as u64;
+        (dx * dx).saturating_add(dy * dy)
+    }
 }
 
 impl IntersectsWith<AABB> for AABB {
@@ -78,6 +174,18 @@ impl IntersectsWith<AABB> for AABB {
     }
 }
 
+impl DistanceTo<AABB> for AABB {
+    /// Calculates the squared distance between the closest points of this and another
+    /// [`AABB`], or zero if the boxes intersect or touch.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to calculate the distance to.
+    #[inline]
+    fn distance_sq_to(&self, other: &AABB) -> u64 {
+        self.squared_distance_to(other)
+    }
+}
+
 impl Add for AABB {
     type Output = Self;
 
@@ -104,6 +212,36 @@ impl From<&[i32; 4]> for AABB {
     }
 }
 
+impl From<(Point, Point)> for AABB {
+    /// Constructs an [`AABB`] from its top-left and bottom-right corners.
+    #[inline]
+    fn from(corners: (Point, Point)) -> Self {
+        Self {
+            tl: corners.0,
+            br: corners.1,
+        }
+    }
+}
+
+impl From<Point> for AABB {
+    /// Constructs a degenerate [`AABB`] covering only the specified point.
+    #[inline]
+    fn from(point: Point) -> Self {
+        Self {
+            tl: point,
+            br: point,
+        }
+    }
+}
+
+impl From<(i32, i32, i32, i32)> for AABB {
+    /// Constructs an [`AABB`] from the coordinates `(x1, y1, x2, y2)` of its edges.
+    #[inline]
+    fn from(rect: (i32, i32, i32, i32)) -> Self {
+        Self::new(rect.0, rect.1, rect.2, rect.3)
+    }
+}
+
 impl Into<[i32; 4]> for AABB {
     fn into(self) -> [i32; 4] {
         [self.tl.x, self.tl.y, self.br.x, self.br.y]
@@ -144,6 +282,24 @@ mod test {
         assert_eq!(aabb.br.y, 4);
     }
 
+    #[test]
+    fn from_points_works() {
+        let aabb = AABB::from((Point::new(1, 2), Point::new(3, 4)));
+        assert_eq!(aabb, AABB::new(1, 2, 3, 4));
+    }
+
+    #[test]
+    fn from_point_works() {
+        let aabb = AABB::from(Point::new(1, 2));
+        assert_eq!(aabb, AABB::new(1, 2, 1, 2));
+    }
+
+    #[test]
+    fn from_tuple_works() {
+        let aabb: AABB = (1, 2, 3, 4).into();
+        assert_eq!(aabb, AABB::new(1, 2, 3, 4));
+    }
+
     #[test]
     fn as_ref_works() {
         let aabb = AABB::new(1, 2, 3, 4);
@@ -153,12 +309,131 @@ mod test {
         }
     }
 
+    #[test]
+    fn from_f32_conservative_works() {
+        let aabb = AABB::from_f32_conservative([-1.5, 0.25], [2.0, 3.75]);
+        assert_eq!(aabb, AABB::new(-2, 0, 2, 4));
+
+        let aabb = AABB::from_f32_conservative([-0.5, -0.5], [-0.25, -0.25]);
+        assert_eq!(aabb, AABB::new(-1, -1, 0, 0));
+    }
+
+    #[test]
+    fn from_f32_conservative_clamps() {
+        let aabb = AABB::from_f32_conservative([-3e9, i32::MIN as f32], [3e9, f32::INFINITY]);
+        assert_eq!(aabb, AABB::new(i32::MIN, i32::MIN, i32::MAX, i32::MAX));
+
+        let aabb = AABB::from_f32_conservative([f32::NAN, 0.0], [f32::NAN, 0.0]);
+        assert_eq!(aabb, AABB::new(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn from_f64_conservative_works() {
+        let aabb = AABB::from_f64_conservative([-1.5, 0.25], [2.0, 3.75]);
+        assert_eq!(aabb, AABB::new(-2, 0, 2, 4));
+
+        let near_max = i32::MAX as f64 - 0.5;
+        let aabb = AABB::from_f64_conservative([near_max, near_max], [near_max + 1.0, 1e12]);
+        assert_eq!(
+            aabb,
+            AABB::new(i32::MAX - 1, i32::MAX - 1, i32::MAX, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn translated_works() {
+        let aabb = AABB::new(1, 2, 3, 4);
+        assert_eq!(aabb.translated(10, -10), AABB::new(11, -8, 13, -6));
+        assert_eq!(aabb.translated(0, 0), aabb);
+    }
+
+    #[test]
+    fn translated_saturates() {
+        let aabb = AABB::new(i32::MAX - 2, i32::MIN + 2, i32::MAX, i32::MIN + 4);
+        assert_eq!(
+            aabb.translated(5, -5),
+            AABB::new(i32::MAX, i32::MIN, i32::MAX, i32::MIN)
+        );
+    }
+
+    #[test]
+    fn flipped_y_works() {
+        let aabb = AABB::new(1, 2, 3, 4);
+        assert_eq!(aabb.flipped_y(), AABB::new(1, -4, 3, -2));
+        assert_eq!(aabb.flipped_y().flipped_y(), aabb);
+        assert_eq!(
+            AABB::new(0, i32::MIN, 0, 0).flipped_y(),
+            AABB::new(0, 0, 0, i32::MAX)
+        );
+    }
+
+    #[test]
+    fn squared_distance_to_point_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb.squared_distance_to_point(&Point::new(5, 5)), 0);
+        assert_eq!(aabb.squared_distance_to_point(&Point::new(10, 0)), 0);
+        assert_eq!(aabb.squared_distance_to_point(&Point::new(-3, 5)), 9);
+        assert_eq!(aabb.squared_distance_to_point(&Point::new(13, 14)), 25);
+    }
+
+    #[test]
+    fn distance_to_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb.distance_sq_to(&AABB::new(5, 5, 20, 20)), 0);
+        assert_eq!(aabb.distance_sq_to(&AABB::new(2, 2, 4, 4)), 0);
+        assert_eq!(aabb.distance_sq_to(&AABB::new(13, 14, 20, 20)), 25);
+        assert_eq!((&aabb).distance_sq_to(&AABB::new(-20, 0, -3, 10)), 9);
+    }
+
+    #[test]
+    fn squared_distance_to_works() {
+        let aabb = AABB::new(0, 0, 10, 10);
+        assert_eq!(aabb.squared_distance_to(&AABB::new(5, 5, 20, 20)), 0);
+        assert_eq!(aabb.squared_distance_to(&AABB::new(10, 0, 20, 10)), 0);
+        assert_eq!(aabb.squared_distance_to(&AABB::new(13, 0, 20, 10)), 9);
+        assert_eq!(aabb.squared_distance_to(&AABB::new(-20, -20, -3, -4)), 25);
+        assert_eq!(
+            AABB::new(i32::MIN, 0, i32::MIN, 0).squared_distance_to(&AABB::new(
+                i32::MAX,
+                0,
+                i32::MAX,
+                0
+            )),
+            (u32::MAX as u64).pow(2)
+        );
+        assert_eq!(
+            AABB::new(i32::MIN, i32::MIN, i32::MIN, i32::MIN).squared_distance_to(&AABB::new(
+                i32::MAX,
+                i32::MAX,
+                i32::MAX,
+                i32::MAX
+            )),
+            u64::MAX
+        );
+    }
+
     #[test]
     fn intersects_with_self_works() {
         let a = AABB::new(0, 0, 1, 1);
         assert!(a.intersects_with(&a));
     }
 
+    #[test]
+    fn intersects_with_reference_works() {
+        fn intersects<T: IntersectsWith<AABB>>(element: T, other: &AABB) -> bool {
+            element.intersects_with(other)
+        }
+
+        let a = AABB::new(0, 0, 2, 2);
+        let b = AABB::new(1, 1, 3, 3);
+        let c = AABB::new(10, 10, 12, 12);
+        assert!(intersects(&a, &b));
+        assert!(!intersects(&a, &c));
+
+        // The referenced box is still usable.
+        assert!(intersects(a, &b));
+    }
+
     #[test]
     fn intersects_when_partial_overlap_works() {
         let a = AABB::new(0, 0, 2, 2);
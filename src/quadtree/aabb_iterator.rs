@@ -0,0 +1,66 @@
+//! Provides an `Iterator` that lazily yields the elements intersecting a bounding box.
+use crate::intersections::IntersectsWith;
+use crate::quadtree::free_list::{self, IndexType};
+use crate::quadtree::node_list::NodeList;
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::{QuadTree, AABB};
+
+/// An iterator over the elements of a [`QuadTree`] whose bounding boxes intersect
+/// a rectangle. Obtained from [`QuadTree::iter_in_aabb()`].
+///
+/// # Remarks
+/// Since every element is stored in exactly one leaf, each matching element is
+/// yielded exactly once without the need to deduplicate.
+pub struct AabbIterator<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    tree: &'a QuadTree<ElementId>,
+    rect: AABB,
+    /// The nodes that are still to be explored.
+    to_process: NodeList,
+    /// The next element node of the current leaf, or `free_list::SENTINEL`
+    /// if the next leaf needs to be found.
+    elem_node_idx: IndexType,
+}
+
+impl<'a, ElementId> AabbIterator<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    pub(crate) fn new(tree: &'a QuadTree<ElementId>, rect: AABB) -> Self {
+        let mut to_process = NodeList::default();
+        to_process.push_back(tree.get_root_node_data());
+        Self {
+            tree,
+            rect,
+            to_process,
+            elem_node_idx: free_list::SENTINEL,
+        }
+    }
+}
+
+impl<'a, ElementId> Iterator for AabbIterator<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    type Item = (ElementId, AABB);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.elem_node_idx != free_list::SENTINEL {
+                let (id, rect, next) = self.tree.element_node_at(self.elem_node_idx);
+                self.elem_node_idx = next;
+
+                // Depending on the size of the quadrant, the candidate element
+                // might still not be covered by the search rectangle.
+                if self.rect.intersects_with(&rect) {
+                    return Some((id, rect));
+                }
+            }
+
+            let leaf = self.tree.next_leaf_aabb(&self.rect, &mut self.to_process)?;
+            self.elem_node_idx = self.tree.first_element_node_of(&leaf);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Provides a query cache for query rectangles that move only slightly between calls.
+use crate::intersections::IntersectsWith;
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::{QuadTree, AABB};
+
+/// Caches the elements around the last query rectangle, such that subsequent
+/// queries with nearby rectangles skip the traversal of the tree, e.g. for a
+/// cursor or a camera that moves slightly every frame.
+///
+/// # Remarks
+/// On a cache miss, the tree is queried with the query rectangle grown by the
+/// configured margin on every side, and the matching elements are stored. Queries
+/// whose rectangle lies within this grown rectangle are answered by testing the
+/// stored elements only. The results are exact in both cases.
+///
+/// The cache is tied to the [`generation()`] of the tree it was filled from
+/// and is refilled whenever the tree changes. Since the generation does not
+/// identify the tree itself, a cache must only be used with a single tree;
+/// call [`invalidate()`] before querying a different one.
+///
+/// [`generation()`]: QuadTree::generation
+/// [`invalidate()`]: CachedQuery::invalidate
+///
+/// # Example
+/// ```rust
+/// use space_partitioning::quadtree::{CachedQuery, QuadRect, QuadTreeElement, AABB};
+/// use space_partitioning::QuadTree;
+///
+/// let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 4, 4, 1);
+/// tree.insert(QuadTreeElement::new(1, AABB::new(10, 10, 20, 20))).unwrap();
+///
+/// let mut cache = CachedQuery::new(16);
+/// assert_eq!(cache.intersect_aabb(&tree, &AABB::new(0, 0, 12, 12)), [1]);
+/// assert_eq!(cache.intersect_aabb(&tree, &AABB::new(2, 2, 8, 8)), []);
+/// assert_eq!(cache.traversals(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachedQuery<ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    /// The margin by which query rectangles are grown on a cache miss.
+    margin: i32,
+    /// The grown rectangle of the last cache miss.
+    region: Option<AABB>,
+    /// The generation of the tree the cache was filled from.
+    generation: u64,
+    /// The elements intersecting the cached region.
+    elements: Vec<(ElementId, AABB)>,
+    /// The number of queries that traversed the tree.
+    traversals: usize,
+}
+
+impl<ElementId> CachedQuery<ElementId>
+where
+    ElementId: ElementIdType,
+{
+    /// Creates an empty cache.
+    ///
+    /// # Arguments
+    /// * [`margin`] - The margin by which query rectangles are grown on a cache miss.
+    ///   Larger margins make cache hits more likely, but more elements are tested on each hit.
+    pub fn new(margin: i32) -> Self {
+        Self {
+            margin,
+            region: None,
+            generation: 0,
+            elements: Vec::new(),
+            traversals: 0,
+        }
+    }
+
+    /// Returns the IDs of all elements of the tree intersecting the specified
+    /// bounding box, traversing the tree only if the cache cannot answer the query.
+    ///
+    /// # Arguments
+    /// * [`tree`] - The tree to query. This must be the tree the cache was filled from,
+    ///   unless the cache was invalidated since.
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb(&mut self, tree: &QuadTree<ElementId>, rect: &AABB) -> Vec<ElementId> {
+        if !self.covers(tree, rect) {
+            let region = AABB::new(
+                rect.tl.x.saturating_sub(self.margin),
+                rect.tl.y.saturating_sub(self.margin),
+                rect.br.x.saturating_add(self.margin),
+                rect.br.y.saturating_add(self.margin),
+            );
+            self.elements.clear();
+            self.elements.extend(tree.iter_in_aabb(&region));
+            self.region = Some(region);
+            self.generation = tree.generation();
+            self.traversals += 1;
+        }
+
+        self.elements
+            .iter()
+            .filter(|(_, elem_rect)| rect.intersects_with(elem_rect))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Returns the number of queries that traversed the tree, i.e. the number of cache misses.
+    #[inline]
+    pub fn traversals(&self) -> usize {
+        self.traversals
+    }
+
+    /// Empties the cache, such that the next query traverses the tree.
+    /// Use this before querying a different tree.
+    pub fn invalidate(&mut self) {
+        self.region = None;
+        self.elements.clear();
+    }
+
+    /// Determines whether the cached elements answer a query for the specified
+    /// rectangle, i.e. whether the rectangle lies within the cached region and
+    /// the tree did not change since.
+    fn covers(&self, tree: &QuadTree<ElementId>, rect: &AABB) -> bool {
+        match &self.region {
+            Some(region) => {
+                self.generation == tree.generation()
+                    && region.tl.x <= rect.tl.x
+                    && region.tl.y <= rect.tl.y
+                    && rect.br.x <= region.br.x
+                    && rect.br.y <= region.br.y
+            }
+            None => false,
+        }
+    }
+}
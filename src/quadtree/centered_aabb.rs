@@ -136,6 +136,19 @@ impl CenteredAABB {
         )
     }
 
+    /// Determines whether this box and the `other` box share a segment of an edge.
+    ///
+    /// # Remarks
+    /// Boxes touching only at a corner do not share an edge. Since quadrants of
+    /// odd-sized boxes are rounded down, a gap of one unit is tolerated.
+    pub(crate) fn shares_edge_with(&self, other: &CenteredAABB) -> bool {
+        let gap_x = self.left().max(other.left()) - self.right().min(other.right());
+        let gap_y = self.top().max(other.top()) - self.bottom().min(other.bottom());
+        let touches_x = (0..=1).contains(&gap_x);
+        let touches_y = (0..=1).contains(&gap_y);
+        (touches_x && gap_y < 0) || (touches_y && gap_x < 0)
+    }
+
     #[inline]
     fn left_half(&self) -> RangeInclusive<i32> {
         self.left()..=self.center_x
@@ -192,4 +205,21 @@ mod test {
     fn aabb_i32_is_16_bytes() {
         assert_eq!(std::mem::size_of::<CenteredAABB>(), 16);
     }
+
+    #[test]
+    fn shares_edge_with_works() {
+        let quadrants = CenteredAABB::from_ltwh(0, 0, 16, 16).split_quadrants();
+        let [_, top_left, top_right, bottom_left, bottom_right] = quadrants;
+        assert!(top_left.shares_edge_with(&top_right));
+        assert!(top_left.shares_edge_with(&bottom_left));
+        assert!(bottom_right.shares_edge_with(&top_right));
+        assert!(bottom_right.shares_edge_with(&bottom_left));
+
+        // Diagonal quadrants only touch at the center.
+        assert!(!top_left.shares_edge_with(&bottom_right));
+        assert!(!top_right.shares_edge_with(&bottom_left));
+
+        let far_away = CenteredAABB::from_ltwh(32, 0, 16, 16);
+        assert!(!top_right.shares_edge_with(&far_away));
+    }
 }
@@ -0,0 +1,106 @@
+//! Provides a read-only cursor for traversing a `QuadTree` manually.
+use crate::quadtree::free_list;
+use crate::quadtree::node_data::NodeData;
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::{QuadTree, AABB};
+
+/// A read-only cursor pointing at a node of a [`QuadTree`], e.g. for custom
+/// traversals with pruning rules not covered by the built-in queries.
+/// Obtained from [`QuadTree::cursor()`], which points at the root.
+///
+/// # Remarks
+/// A branch has five children: the first child stores the elements that span
+/// multiple quadrants of the branch and covers the same area as the branch itself.
+/// It is followed by the top-left, top-right, bottom-left and bottom-right quadrants.
+/// Elements are only stored in leaves, and every element is stored in exactly one leaf.
+pub struct QuadTreeCursor<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    tree: &'a QuadTree<ElementId>,
+    nd: NodeData,
+}
+
+impl<'a, ElementId> QuadTreeCursor<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    pub(crate) fn new(tree: &'a QuadTree<ElementId>, nd: NodeData) -> Self {
+        Self { tree, nd }
+    }
+
+    /// Gets the bounding box of the node.
+    #[inline]
+    pub fn aabb(&self) -> AABB {
+        self.nd.crect.get_aabb()
+    }
+
+    /// Gets the depth of the node. The root is at depth zero; the first child of
+    /// a branch, which stores the elements spanning its quadrants, shares the
+    /// depth of the branch.
+    #[inline]
+    pub fn depth(&self) -> u8 {
+        self.nd.depth
+    }
+
+    /// Determines whether the node is a leaf, i.e. stores elements rather than children.
+    #[inline]
+    pub fn is_leaf(&self) -> bool {
+        self.tree.node_of(&self.nd).is_leaf()
+    }
+
+    /// Gets the number of elements stored in the node, which is zero for branches.
+    #[inline]
+    pub fn element_count(&self) -> u32 {
+        let node = self.tree.node_of(&self.nd);
+        if node.is_leaf() {
+            node.element_count
+        } else {
+            0
+        }
+    }
+
+    /// Returns cursors pointing at the five children of a branch, or an empty
+    /// list if the node is a leaf.
+    pub fn children(&self) -> Vec<QuadTreeCursor<'a, ElementId>> {
+        let node = self.tree.node_of(&self.nd);
+        if node.is_leaf() {
+            return Vec::new();
+        }
+
+        let first_child_index = node.get_first_child_node_index();
+        let quadrants = self.nd.crect.split_quadrants();
+        quadrants
+            .iter()
+            .enumerate()
+            .map(|(offset, crect)| {
+                // The "this" node is at the same depth and cannot split.
+                let is_this_node = offset == 0;
+                let depth = self.nd.depth + (!is_this_node) as u8;
+                let index = first_child_index + offset as u32;
+                let nd = NodeData::new(*crect, index, depth, !is_this_node);
+                QuadTreeCursor::new(self.tree, nd)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the IDs and bounding boxes of the elements
+    /// stored in the node, which is empty for branches.
+    pub fn elements(&self) -> impl Iterator<Item = (ElementId, AABB)> + 'a {
+        let tree = self.tree;
+        let mut elem_node_idx = if self.is_leaf() {
+            tree.first_element_node_of(&self.nd)
+        } else {
+            free_list::SENTINEL
+        };
+
+        std::iter::from_fn(move || {
+            if elem_node_idx == free_list::SENTINEL {
+                return None;
+            }
+            let (id, rect, next) = tree.element_node_at(elem_node_idx);
+            elem_node_idx = next;
+            Some((id, rect))
+        })
+    }
+}
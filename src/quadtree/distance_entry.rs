@@ -0,0 +1,59 @@
+use std::cmp::Ordering;
+
+/// An item ordered by its distance, such that a `BinaryHeap` of entries
+/// pops the closest item first.
+#[derive(Debug)]
+pub(crate) struct DistanceEntry<T> {
+    /// The (squared) distance of the item.
+    pub distance: u64,
+    /// The item.
+    pub item: T,
+}
+
+impl<T> DistanceEntry<T> {
+    #[inline]
+    pub fn new(distance: u64, item: T) -> Self {
+        Self { distance, item }
+    }
+}
+
+impl<T> PartialEq for DistanceEntry<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl<T> Eq for DistanceEntry<T> {}
+
+impl<T> PartialOrd for DistanceEntry<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for DistanceEntry<T> {
+    /// Orders entries by descending distance, turning a max-heap into a min-heap.
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.cmp(&self.distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn heap_pops_closest_first() {
+        let mut heap = BinaryHeap::new();
+        heap.push(DistanceEntry::new(3, 'c'));
+        heap.push(DistanceEntry::new(1, 'a'));
+        heap.push(DistanceEntry::new(2, 'b'));
+        assert_eq!(heap.pop().unwrap().item, 'a');
+        assert_eq!(heap.pop().unwrap().item, 'b');
+        assert_eq!(heap.pop().unwrap().item, 'c');
+    }
+}
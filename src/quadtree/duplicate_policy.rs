@@ -0,0 +1,20 @@
+/// Determines how a [`QuadTree`] handles the insertion of an element whose ID
+/// is already stored, as set by [`QuadTree::set_duplicate_policy()`].
+///
+/// [`QuadTree`]: crate::quadtree::QuadTree
+/// [`QuadTree::set_duplicate_policy()`]: crate::quadtree::QuadTree::set_duplicate_policy
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum DuplicatePolicy {
+    /// The element is inserted as an independent element, such that both elements
+    /// are reported by queries. The tree does not look for existing elements,
+    /// which keeps inserts fast.
+    #[default]
+    Allow,
+    /// The insert fails with [`InsertError::DuplicateId`].
+    ///
+    /// [`InsertError::DuplicateId`]: crate::quadtree::InsertError::DuplicateId
+    Reject,
+    /// The existing element is moved to the bounding box of the inserted one
+    /// and takes over its layers and z-order.
+    Replace,
+}
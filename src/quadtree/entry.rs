@@ -0,0 +1,130 @@
+use crate::quadtree::error::InsertError;
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::{QuadTree, QuadTreeElement, AABB};
+
+/// A view into a single element of a [`QuadTree`], which may either be
+/// occupied or vacant. Obtained from [`QuadTree::entry()`].
+pub enum Entry<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    /// An element with the requested ID is already stored in the tree.
+    Occupied(OccupiedEntry<'a, ElementId>),
+    /// No element with the requested ID is stored in the tree.
+    Vacant(VacantEntry<'a, ElementId>),
+}
+
+/// A view into an element that is stored in the tree.
+pub struct OccupiedEntry<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    tree: &'a mut QuadTree<ElementId>,
+    element: QuadTreeElement<ElementId>,
+}
+
+/// A view into an element that is not stored in the tree yet.
+pub struct VacantEntry<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    tree: &'a mut QuadTree<ElementId>,
+    element: QuadTreeElement<ElementId>,
+}
+
+impl<'a, ElementId> Entry<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    /// Returns whether an element with the requested ID is already stored in the tree.
+    #[inline]
+    pub fn is_occupied(&self) -> bool {
+        matches!(self, Self::Occupied(_))
+    }
+
+    /// Returns the ID of the entry.
+    #[inline]
+    pub fn id(&self) -> &ElementId {
+        match self {
+            Self::Occupied(entry) => entry.id(),
+            Self::Vacant(entry) => entry.id(),
+        }
+    }
+
+    /// Inserts the element if the entry is vacant. Does nothing if the
+    /// entry is occupied.
+    ///
+    /// # Returns
+    /// The bounding box of the element as it is stored in the tree.
+    pub fn or_insert(self) -> Result<AABB, InsertError> {
+        match self {
+            Self::Occupied(entry) => Ok(*entry.rect()),
+            Self::Vacant(entry) => {
+                let rect = *entry.rect();
+                entry.insert().map(|_| rect)
+            }
+        }
+    }
+}
+
+impl<'a, ElementId> OccupiedEntry<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    #[inline]
+    pub(crate) fn new(
+        tree: &'a mut QuadTree<ElementId>,
+        element: QuadTreeElement<ElementId>,
+    ) -> Self {
+        Self { tree, element }
+    }
+
+    /// Returns the ID of the element.
+    #[inline]
+    pub fn id(&self) -> &ElementId {
+        &self.element.id
+    }
+
+    /// Returns the bounding box the element is currently stored with.
+    #[inline]
+    pub fn rect(&self) -> &AABB {
+        &self.element.rect
+    }
+
+    /// Removes the element from the tree and returns it.
+    pub fn remove(self) -> QuadTreeElement<ElementId> {
+        let removed = self.tree.remove(&self.element);
+        debug_assert!(removed);
+        self.element
+    }
+}
+
+impl<'a, ElementId> VacantEntry<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    #[inline]
+    pub(crate) fn new(
+        tree: &'a mut QuadTree<ElementId>,
+        element: QuadTreeElement<ElementId>,
+    ) -> Self {
+        Self { tree, element }
+    }
+
+    /// Returns the ID of the element.
+    #[inline]
+    pub fn id(&self) -> &ElementId {
+        &self.element.id
+    }
+
+    /// Returns the bounding box the element will be inserted with.
+    #[inline]
+    pub fn rect(&self) -> &AABB {
+        &self.element.rect
+    }
+
+    /// Inserts the element into the tree.
+    pub fn insert(self) -> Result<(), InsertError> {
+        self.tree.insert(self.element)
+    }
+}
@@ -4,12 +4,21 @@ use std::{error, fmt};
 pub enum InsertError {
     /// The element that was about to be inserted was outside of the bounds of the QuadTree.
     OutOfBounds,
+    /// The tree already holds the maximum number of elements it was configured to accept.
+    CapacityExceeded,
+    /// An element with the same ID is already stored and the tree's
+    /// [`DuplicatePolicy`](crate::quadtree::DuplicatePolicy) rejects duplicates.
+    DuplicateId,
 }
 
 impl fmt::Display for InsertError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Self::OutOfBounds => write!(f, "the element was outside of the tree bounds"),
+            Self::CapacityExceeded => {
+                write!(f, "the tree has reached its maximum number of elements")
+            }
+            Self::DuplicateId => write!(f, "an element with the same ID was already inserted"),
         }
     }
 }
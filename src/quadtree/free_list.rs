@@ -11,13 +11,16 @@ where
     T: Default,
 {
     /// The number of live elements in the list.
-    #[cfg(debug_assertions)]
     length: usize,
     /// The actual data.
     data: Vec<FreeElement<T>>,
     /// The index of the the most recently freed element, or `SENTINEL` if no
     /// element is free.
     first_free: IndexType,
+    /// Marks the erased elements in debug builds in order to detect accesses
+    /// to erased indices, which would otherwise be undefined behavior.
+    #[cfg(debug_assertions)]
+    poisoned: Vec<bool>,
 }
 
 union FreeElement<T> {
@@ -36,8 +39,9 @@ where
         Self {
             data: Vec::default(),
             first_free: SENTINEL,
-            #[cfg(debug_assertions)]
             length: 0,
+            #[cfg(debug_assertions)]
+            poisoned: Vec::default(),
         }
     }
 }
@@ -46,12 +50,20 @@ impl<T> FreeList<T>
 where
     T: Default,
 {
+    /// Creates an empty list with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+            first_free: SENTINEL,
+            length: 0,
+            #[cfg(debug_assertions)]
+            poisoned: Vec::with_capacity(capacity),
+        }
+    }
+
     /// Inserts an element to the free list and returns an index to it.
     pub fn insert(&mut self, element: T) -> IndexType {
-        #[cfg(debug_assertions)]
-        {
-            self.length += 1;
-        }
+        self.length += 1;
 
         return if self.first_free != SENTINEL {
             let index = self.first_free;
@@ -61,25 +73,29 @@ where
 
             // Place the element into the previously free location.
             self.data[index as usize].element = ManuallyDrop::new(element);
+            #[cfg(debug_assertions)]
+            {
+                self.poisoned[index as usize] = false;
+            }
             index
         } else {
             let fe = FreeElement {
                 element: ManuallyDrop::new(element),
             };
             self.data.push(fe);
+            #[cfg(debug_assertions)]
+            self.poisoned.push(false);
             (self.data.len() - 1) as IndexType
         };
     }
 
     /// Removes the nth element from the free list.
     pub fn erase(&mut self, n: IndexType) {
-        self.first_free = SENTINEL;
         if self.data.is_empty() {
             return;
         }
-        debug_assert!(!self.debug_is_in_free_list(n));
+        self.debug_assert_not_poisoned(n);
 
-        #[cfg(debug_assertions)]
         debug_assert!(self.length > 0);
 
         unsafe { ManuallyDrop::drop(&mut self.data[n as usize].element) };
@@ -88,8 +104,10 @@ where
 
         #[cfg(debug_assertions)]
         {
-            self.length -= 1;
+            self.poisoned[n as usize] = true;
         }
+
+        self.length -= 1;
     }
 
     /// Removes all elements from the free list.
@@ -127,10 +145,42 @@ where
         // list can be trivially cleared.
         self.data.clear();
         self.first_free = SENTINEL;
+        #[cfg(debug_assertions)]
+        self.poisoned.clear();
 
+        self.length = 0;
+    }
+
+    /// Removes the erased elements from the end of the list and releases unused memory.
+    /// The indices of the remaining elements stay valid.
+    ///
+    /// # Remarks
+    /// Erased elements followed by live elements cannot be removed without invalidating
+    /// indices; they remain available for reuse, lowest index first.
+    pub fn shrink_to_fit(&mut self) {
+        let mut is_free = vec![false; self.data.len()];
+        let mut token = self.first_free;
+        while token != SENTINEL {
+            is_free[token as usize] = true;
+            token = unsafe { self.data[token as usize].next };
+        }
+
+        // Free entries only hold an index, so truncating them drops nothing.
+        let len = is_free.iter().rposition(|free| !free).map_or(0, |i| i + 1);
+        self.data.truncate(len);
+        self.data.shrink_to_fit();
         #[cfg(debug_assertions)]
         {
-            self.length = 0;
+            self.poisoned.truncate(len);
+            self.poisoned.shrink_to_fit();
+        }
+
+        self.first_free = SENTINEL;
+        for index in (0..len).rev() {
+            if is_free[index] {
+                self.data[index].next = self.first_free;
+                self.first_free = index as IndexType;
+            }
         }
     }
 
@@ -140,11 +190,11 @@ where
     ///
     /// If the element at the specified index was erased, the union now acts
     ///  as a pointer to the next free element. Accessing the same index again after that.
-    ///  is undefined behavior.
+    ///  is undefined behavior. In debug builds, such an access panics instead.
     #[inline]
     pub unsafe fn at(&self, index: IndexType) -> &T {
         debug_assert_ne!(index, SENTINEL);
-        debug_assert!(!self.debug_is_in_free_list(index));
+        self.debug_assert_not_poisoned(index);
         &self.data[index as usize].element
     }
 
@@ -154,11 +204,11 @@ where
     ///
     /// If the element at the specified index was erased, the union now acts
     /// as a pointer to the next free element. Accessing the same index again after that.
-    /// is undefined behavior.
+    /// is undefined behavior. In debug builds, such an access panics instead.
     #[inline]
     pub unsafe fn at_mut(&mut self, index: IndexType) -> &mut T {
         debug_assert_ne!(index, SENTINEL);
-        debug_assert!(!self.debug_is_in_free_list(index));
+        self.debug_assert_not_poisoned(index);
         &mut self.data[index as usize].element
     }
 
@@ -168,13 +218,25 @@ where
         self.data.len()
     }
 
-    /// Gets the number of elements in the list.
+    /// Gets the number of elements the list can hold without reallocating.
     #[allow(dead_code)]
-    pub fn debug_len(&self) -> usize {
+    pub fn reserved_capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Gets the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Panics in debug builds if the element at the specified index was erased.
+    #[inline]
+    #[allow(unused_variables)]
+    fn debug_assert_not_poisoned(&self, n: IndexType) {
         #[cfg(debug_assertions)]
-        return self.length;
-        #[cfg(not(debug_assertions))]
-        unimplemented!()
+        if self.poisoned[n as usize] {
+            panic!("index {} of the free list refers to an erased element", n);
+        }
     }
 
     #[allow(dead_code, unused_variables)]
@@ -263,6 +325,22 @@ mod test {
         assert_eq!(list.capacity(), 2);
     }
 
+    #[test]
+    fn erased_indices_are_reused() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 3);
+        list.erase(0);
+        list.erase(1);
+        assert!(list.debug_is_in_free_list(0));
+        assert!(list.debug_is_in_free_list(1));
+
+        // Indices are reused in reverse order of erasure.
+        assert_eq!(list.insert(Complex::default()), 1);
+        assert_eq!(list.insert(Complex::default()), 0);
+        assert_eq!(list.insert(Complex::default()), 3);
+        assert_eq!(list.len(), 4);
+    }
+
     #[test]
     fn erase_in_ascending_order() {
         let mut list = FreeList::<Complex>::default();
@@ -310,6 +388,39 @@ mod test {
         assert_eq!(list.capacity(), 0);
     }
 
+    #[test]
+    fn shrink_to_fit_removes_trailing_erased_elements() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 6);
+        list.erase(1);
+        list.erase(5);
+        list.erase(3);
+        list.erase(4);
+
+        list.shrink_to_fit();
+        assert_eq!(list.capacity(), 3);
+        assert_eq!(list.reserved_capacity(), 3);
+        assert_eq!(list.len(), 2);
+        assert!(list.debug_is_in_free_list(1));
+        assert_eq!(unsafe { list.at(2) }, &Complex::default());
+
+        // The remaining hole is reused before the list grows again.
+        assert_eq!(list.insert(Complex::default()), 1);
+        assert_eq!(list.insert(Complex::default()), 3);
+    }
+
+    #[test]
+    fn shrink_to_fit_of_empty_list_works() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(0);
+        list.erase(1);
+        list.shrink_to_fit();
+        assert_eq!(list.capacity(), 0);
+        assert_eq!(list.first_free, SENTINEL);
+        assert_eq!(list.insert(Complex::default()), 0);
+    }
+
     #[test]
     fn is_in_free_list_works() {
         let mut list = FreeList::<Complex>::default();
@@ -343,6 +454,48 @@ mod test {
         assert_eq!(*element, Complex(0., 0.));
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "index 0 of the free list refers to an erased element")]
+    fn at_erased_index_panics_in_debug_builds() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(0);
+        let _ = unsafe { list.at(0) };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "index 1 of the free list refers to an erased element")]
+    fn at_mut_erased_index_panics_in_debug_builds() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(1);
+        let _ = unsafe { list.at_mut(1) };
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "index 0 of the free list refers to an erased element")]
+    fn erasing_twice_panics_in_debug_builds() {
+        let mut list = FreeList::<Complex>::default();
+        insert_some(&mut list, 2);
+        list.erase(0);
+        list.erase(0);
+    }
+
+    #[test]
+    fn with_capacity_works() {
+        let mut list = FreeList::<Complex>::with_capacity(16);
+        assert_eq!(list.capacity(), 0);
+        assert!(list.reserved_capacity() >= 16);
+
+        let reserved = list.reserved_capacity();
+        insert_some(&mut list, 16);
+        assert_eq!(list.capacity(), 16);
+        assert_eq!(list.reserved_capacity(), reserved);
+    }
+
     fn insert_some(list: &mut FreeList<Complex>, n: usize) {
         for _ in 0..n {
             list.insert(Complex::default());
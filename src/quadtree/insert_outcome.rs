@@ -0,0 +1,8 @@
+/// Describes the effect an insertion had on the structure of the tree.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct InsertOutcome {
+    /// The number of leaves that were split in order to store the element.
+    pub splits_performed: u32,
+    /// The depth of the leaf the element was stored in.
+    pub final_depth: u8,
+}
@@ -0,0 +1,69 @@
+/// Calculates the Morton code (Z-order curve index) of a point.
+///
+/// # Remarks
+/// The bits of the X coordinate are stored at even, the bits of the Y coordinate at odd
+/// positions. Coordinates are biased such that the ordering of the codes follows
+/// the ordering of the signed coordinates.
+///
+/// # Example
+/// ```rust
+/// use space_partitioning::quadtree::morton_code;
+/// assert!(morton_code(0, 0) < morton_code(1, 0));
+/// assert!(morton_code(1, 0) < morton_code(0, 1));
+/// assert!(morton_code(-1, -1) < morton_code(0, 0));
+/// ```
+#[inline]
+pub fn morton_code(x: i32, y: i32) -> u64 {
+    spread_bits(bias(x)) | (spread_bits(bias(y)) << 1)
+}
+
+/// Maps an `i32` onto an `u32` such that the order of values is retained.
+#[inline]
+fn bias(value: i32) -> u32 {
+    (value as u32) ^ 0x8000_0000
+}
+
+/// Spreads the bits of a value onto the even bits of the result.
+#[inline]
+fn spread_bits(value: u32) -> u64 {
+    let mut x = value as u64;
+    x = (x | (x << 16)) & 0x0000_FFFF_0000_FFFF;
+    x = (x | (x << 8)) & 0x00FF_00FF_00FF_00FF;
+    x = (x | (x << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    x = (x | (x << 1)) & 0x5555_5555_5555_5555;
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spread_bits_works() {
+        assert_eq!(spread_bits(0b1011), 0b1000101);
+        assert_eq!(spread_bits(u32::MAX), 0x5555_5555_5555_5555);
+    }
+
+    #[test]
+    fn morton_code_follows_z_order() {
+        let codes = [
+            morton_code(0, 0),
+            morton_code(1, 0),
+            morton_code(0, 1),
+            morton_code(1, 1),
+            morton_code(2, 0),
+        ];
+        for pair in codes.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+    }
+
+    #[test]
+    fn morton_code_handles_negative_values() {
+        assert!(morton_code(-1, -1) < morton_code(0, 0));
+        assert!(morton_code(i32::MIN, i32::MIN) < morton_code(-1, -1));
+        assert_eq!(morton_code(i32::MIN, i32::MIN), 0);
+        assert_eq!(morton_code(i32::MAX, i32::MAX), u64::MAX);
+    }
+}
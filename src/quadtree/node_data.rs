@@ -4,7 +4,7 @@ use crate::quadtree::AABB;
 
 pub type NodeIndexType = u32;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[repr(C, align(8))]
 pub struct NodeData {
     /// The centered AABB of the the node: center x, center y, width and height.
@@ -18,7 +18,7 @@ pub struct NodeData {
 
 const CAN_SPLIT_FLAG: u8 = 1;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[repr(C, align(1))]
 struct NodeFlags {
     flags: u8,
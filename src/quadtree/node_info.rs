@@ -2,7 +2,7 @@ use crate::quadtree::node::NodeElementCountType;
 use crate::quadtree::node_data::NodeData;
 use crate::quadtree::AABB;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NodeInfo {
     /// The node data.
     pub(crate) nd: NodeData,
@@ -1,3 +1,6 @@
+use crate::intersections::{DistanceTo, IntersectsWith};
+use crate::quadtree::AABB;
+
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Point {
     pub x: i32,
@@ -10,6 +13,29 @@ impl Point {
     }
 }
 
+impl IntersectsWith<AABB> for Point {
+    /// Tests whether this [`Point`] lies within an [`AABB`], including its edges.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to test for intersection.
+    #[inline]
+    fn intersects_with(&self, other: &AABB) -> bool {
+        AABB::from(*self).intersects_with(other)
+    }
+}
+
+impl DistanceTo<AABB> for Point {
+    /// Calculates the squared distance of this [`Point`] to an [`AABB`],
+    /// or zero if the point lies within the box, including its edges.
+    ///
+    /// # Arguments
+    /// * [`other`] - The AABB to calculate the distance to.
+    #[inline]
+    fn distance_sq_to(&self, other: &AABB) -> u64 {
+        other.squared_distance_to_point(self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -18,4 +44,22 @@ mod test {
     fn point_is_8_bytes() {
         assert_eq!(std::mem::size_of::<Point>(), 8);
     }
+
+    #[test]
+    fn intersects_with_works() {
+        let rect = AABB::new(0, 0, 10, 10);
+        assert!(Point::new(5, 5).intersects_with(&rect));
+        assert!(Point::new(10, 0).intersects_with(&rect));
+        assert!(!Point::new(11, 5).intersects_with(&rect));
+        assert!((&Point::new(5, 5)).intersects_with(&rect));
+    }
+
+    #[test]
+    fn distance_to_works() {
+        let rect = AABB::new(0, 0, 10, 10);
+        assert_eq!(Point::new(5, 5).distance_sq_to(&rect), 0);
+        assert_eq!(Point::new(10, 0).distance_sq_to(&rect), 0);
+        assert_eq!(Point::new(-3, 5).distance_sq_to(&rect), 9);
+        assert_eq!(Point::new(13, 14).distance_sq_to(&rect), 25);
+    }
 }
@@ -1,20 +1,36 @@
-use crate::intersections::IntersectsWith;
+use crate::intersections::{IntersectsWith, Ray};
 use crate::quadtree::aabb::AABB;
+use crate::quadtree::aabb_iterator::AabbIterator;
 use crate::quadtree::centered_aabb::CenteredAABB;
+use crate::quadtree::cursor::QuadTreeCursor;
+use crate::quadtree::distance_entry::DistanceEntry;
+use crate::quadtree::duplicate_policy::DuplicatePolicy;
+use crate::quadtree::entry::{Entry, OccupiedEntry, VacantEntry};
 use crate::quadtree::error::InsertError;
 use crate::quadtree::free_list::{self, FreeList, IndexType};
+use crate::quadtree::insert_outcome::InsertOutcome;
+use crate::quadtree::morton::morton_code;
 use crate::quadtree::node::Node;
 use crate::quadtree::node_data::{NodeData, NodeIndexType};
 use crate::quadtree::node_info::NodeInfo;
 use crate::quadtree::node_list::NodeList;
+use crate::quadtree::point::Point;
 use crate::quadtree::quad_rect::QuadRect;
 use crate::quadtree::quadrants::Quadrants;
-use crate::quadtree::quadtree_element::QuadTreeElementNode;
+use crate::quadtree::quadtree_element::{ElementData, QuadTreeElementNode};
 pub use crate::quadtree::quadtree_element::{ElementIdType, QuadTreeElement};
+use crate::quadtree::query_stats::QueryStats;
+use crate::quadtree::translated_view::TranslatedView;
+use crate::quadtree::uniform_grid::UniformGrid;
+use fixedbitset::FixedBitSet;
 use smallvec::SmallVec;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
 // TODO: Add range query: Query using intersect_aabb() or intersect_generic()
 
+/// The layer mask of elements inserted without one, i.e. all layers.
+const ALL_LAYERS: u32 = u32::MAX;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 enum FindLeafHint {
     /// A tree query, e.g. an intersection test.
@@ -25,6 +41,14 @@ enum FindLeafHint {
     Mutate,
 }
 
+/// A node or element queued for expansion in a nearest-neighbor search.
+enum NearestCandidate {
+    /// A node and the bounds of the elements it can contain.
+    Node(NodeData, AABB),
+    /// The index of an element.
+    Element(free_list::IndexType),
+}
+
 /// A QuadTree implementation as described in [Efficient Quadtrees](https://stackoverflow.com/a/48330314/195651).
 ///
 /// # Remarks
@@ -36,9 +60,12 @@ where
     /// Stores all the IDs fo the elements in the quadtree.
     /// An element is only inserted once to the quadtree no matter how many cells it occupies.
     element_ids: FreeList<ElementId>,
-    /// Stores all the rectangles of the elements in the quadtree.
-    /// An element is only inserted once to the quadtree no matter how many cells it occupies.
-    element_rects: FreeList<AABB>,
+    /// Stores the rectangles and the remaining data of the elements in the quadtree,
+    /// at the same indexes as their IDs.
+    element_data: FreeList<ElementData>,
+    /// Stores the indexes of the elements with each ID, in insertion order.
+    /// Used to look up elements by ID without visiting the tree.
+    element_slots: HashMap<ElementId, SmallVec<[free_list::IndexType; 1]>>,
     /// Stores all the element nodes in the quadtree.
     /// For each cell occupied by a `QuadTreeElement`, we store
     /// a `QuadTreeElementNode`.
@@ -59,6 +86,14 @@ where
     smallest_cell_size: u32,
     /// Stores the maximum depth allowed for the quadtree.
     max_depth: u8,
+    /// Stores the maximum number of elements the tree accepts, if any.
+    max_elements: Option<usize>,
+    /// Stores how inserts of IDs that are already stored are handled.
+    duplicate_policy: DuplicatePolicy,
+    /// Stores the sequence number to assign to the next inserted element.
+    next_seq: u64,
+    /// Stores a counter that is incremented whenever the tree is modified.
+    generation: u64,
 }
 
 impl<ElementId> QuadTree<ElementId>
@@ -74,34 +109,407 @@ where
         max_depth: u8,
         max_num_elements: u32,
         smallest_cell_size: u32,
+    ) -> Self {
+        Self::with_capacity(
+            root_rect,
+            max_depth,
+            max_num_elements,
+            smallest_cell_size,
+            0,
+            0,
+        )
+    }
+
+    /// Creates a new tree with storage pre-allocated for the specified number
+    /// of elements and element references.
+    ///
+    /// # Remarks
+    /// Every element is referenced by exactly one leaf, so the number of references
+    /// (see [`references()`]) equals the number of elements. While a leaf is split,
+    /// one additional reference is needed temporarily.
+    ///
+    /// # Arguments
+    /// * [`root_rect`] - The extents of the tree.
+    /// * [`max_depth`] - The maximum depth of the tree.
+    /// * [`max_num_elements`] - The maximum number of elements in a node before it splits.
+    /// * [`smallest_cell_size`] - The smallest size of a cell. If it exceeds half of the
+    ///   extents of the tree, the tree never splits; see [`effective_max_depth()`].
+    /// * [`element_capacity`] - The number of elements to reserve space for.
+    /// * [`reference_capacity`] - The number of element references to reserve space for.
+    pub fn with_capacity(
+        root_rect: QuadRect,
+        max_depth: u8,
+        max_num_elements: u32,
+        smallest_cell_size: u32,
+        element_capacity: usize,
+        reference_capacity: usize,
     ) -> Self {
         assert!(max_num_elements > 0);
         assert!(smallest_cell_size > 0);
         Self {
-            element_ids: FreeList::default(),
-            element_rects: FreeList::default(),
-            element_nodes: FreeList::default(),
+            element_ids: FreeList::with_capacity(element_capacity),
+            element_data: FreeList::with_capacity(element_capacity),
+            element_slots: HashMap::with_capacity(element_capacity),
+            element_nodes: FreeList::with_capacity(reference_capacity),
             nodes: vec![Node::default()],
             root_rect,
             free_node: free_list::SENTINEL,
             max_depth,
             max_num_elements,
             smallest_cell_size,
+            max_elements: None,
+            duplicate_policy: DuplicatePolicy::default(),
+            next_seq: 0,
+            generation: 0,
+        }
+    }
+
+    /// Returns the number of elements stored in the tree.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.element_ids.len()
+    }
+
+    /// Returns a counter that is incremented whenever elements are inserted,
+    /// removed or updated, or the tree is compacted.
+    ///
+    /// # Remarks
+    /// Caches derived from the tree can compare the generation to the one they
+    /// were built with in order to skip recomputation. Operations that do not
+    /// change the tree, such as removing an element that does not exist, keep
+    /// the generation.
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns whether the tree stores no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum number of elements the tree accepts, if any.
+    #[inline]
+    pub fn max_elements(&self) -> Option<usize> {
+        self.max_elements
+    }
+
+    /// Returns the deepest level the tree can subdivide to, given its extents, its
+    /// smallest cell size and its maximum depth.
+    ///
+    /// # Remarks
+    /// A smallest cell size exceeding half of the extents of the tree prevents the
+    /// root from ever splitting, which is reported as an effective maximum depth of zero.
+    /// Use this to detect configurations that effectively disable subdivision.
+    pub fn effective_max_depth(&self) -> u8 {
+        let mut nd = self.get_root_node_data();
+        while nd.can_split_further(self.smallest_cell_size, self.max_depth) {
+            // All quadrants of a node have the same size.
+            let [_, top_left, ..] = nd.crect.split_quadrants();
+            nd = NodeData::new(top_left, nd.index, nd.depth + 1, true);
+        }
+        nd.depth
+    }
+
+    /// Limits the number of elements the tree accepts. Once the limit is reached,
+    /// inserts fail with [`InsertError::CapacityExceeded`]. By default, the number
+    /// of elements is not limited.
+    ///
+    /// # Remarks
+    /// Lowering the limit below the current number of elements does not remove
+    /// any elements; it only prevents further inserts.
+    ///
+    /// # Arguments
+    /// * [`max_elements`] - The maximum number of elements, or `None` to remove the limit.
+    pub fn set_max_elements(&mut self, max_elements: Option<usize>) {
+        self.max_elements = max_elements;
+    }
+
+    /// Returns how inserts of IDs that are already stored are handled.
+    #[inline]
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.duplicate_policy
+    }
+
+    /// Sets how inserts of IDs that are already stored are handled.
+    /// By default, duplicates are allowed.
+    ///
+    /// # Remarks
+    /// The policies other than [`DuplicatePolicy::Allow`] look up the ID of every
+    /// inserted element. Changing the policy does not affect the elements that are already stored.
+    ///
+    /// # Arguments
+    /// * [`policy`] - The policy to apply to future inserts.
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicate_policy = policy;
+    }
+
+    /// Changes the maximum number of elements in a node before it splits, adapting
+    /// the existing nodes to the new threshold.
+    ///
+    /// # Remarks
+    /// When the threshold is lowered, all leaves holding more elements than the new
+    /// threshold are split as long as the tree's maximum depth and smallest cell size
+    /// allow it. When it is raised, sparse branches are collapsed as by [`cleanup()`].
+    ///
+    /// # Arguments
+    /// * [`max_num_elements`] - The new maximum number of elements in a node.
+    ///
+    /// # Returns
+    /// `Err(())` if [`max_num_elements`] is zero; the tree is unchanged in this case.
+    #[allow(clippy::result_unit_err)]
+    pub fn set_max_num_elements(&mut self, max_num_elements: u32) -> Result<(), ()> {
+        if max_num_elements == 0 {
+            return Err(());
+        }
+
+        let previous = self.max_num_elements;
+        self.max_num_elements = max_num_elements;
+        if max_num_elements > previous {
+            self.cleanup();
+        } else if max_num_elements < previous {
+            self.split_full_leaves();
+        }
+        Ok(())
+    }
+
+    /// Splits all leaves holding more than `max_num_elements` elements, as long as
+    /// they can be split further.
+    fn split_full_leaves(&mut self) {
+        let mut tree_split = false;
+        let mut to_process = vec![self.get_root_node_data()];
+        while let Some(nd) = to_process.pop() {
+            let node = &self.nodes[nd.index as usize];
+            if node.is_leaf() {
+                let is_full = node.element_count > self.max_num_elements;
+                if !is_full || !nd.can_split_further(self.smallest_cell_size, self.max_depth) {
+                    continue;
+                }
+                self.distribute_elements_to_child_nodes(&nd);
+                tree_split = true;
+            }
+
+            // The "this" node at offset 0 cannot be split.
+            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+            let split_quadrants = nd.crect.split_quadrants();
+            for (offset, crect) in split_quadrants.iter().enumerate().skip(1) {
+                to_process.push(NodeData::new(
+                    *crect,
+                    fc + offset as u32,
+                    nd.depth + 1,
+                    true,
+                ));
+            }
+        }
+
+        if tree_split {
+            self.generation += 1;
+        }
+    }
+
+    /// Creates a new tree and inserts the specified elements in the Z-order (Morton order)
+    /// of their centers. This places spatially close elements close to each other
+    /// in memory, improving the cache behavior of subsequent queries.
+    ///
+    /// # Arguments
+    /// * [`root_rect`] - The extents of the tree.
+    /// * [`max_depth`] - The maximum depth of the tree.
+    /// * [`max_num_elements`] - The maximum number of elements in a node before it splits.
+    /// * [`smallest_cell_size`] - The smallest size of a cell.
+    /// * [`elements`] - The elements to insert.
+    pub fn from_elements_sorted(
+        root_rect: QuadRect,
+        max_depth: u8,
+        max_num_elements: u32,
+        smallest_cell_size: u32,
+        elements: &[QuadTreeElement<ElementId>],
+    ) -> Result<Self, InsertError> {
+        let mut sorted: Vec<_> = elements.iter().collect();
+        sorted.sort_by_cached_key(|element| {
+            let rect = &element.rect;
+            let cx = (rect.tl.x as i64 + rect.br.x as i64) >> 1;
+            let cy = (rect.tl.y as i64 + rect.br.y as i64) >> 1;
+            morton_code(cx as i32, cy as i32)
+        });
+
+        let mut tree = Self::new(root_rect, max_depth, max_num_elements, smallest_cell_size);
+        for element in sorted {
+            tree.insert(element.clone())?;
         }
+        Ok(tree)
     }
 
+    /// Inserts an element into the tree.
+    ///
+    /// # Remarks
+    /// If an element with the same ID is already stored, the element is handled
+    /// according to the tree's [`duplicate_policy()`].
+    ///
+    /// # Arguments
+    /// * [`element`] - The element to insert.
     pub fn insert(&mut self, element: QuadTreeElement<ElementId>) -> Result<(), InsertError> {
-        let element_coords = &element.rect;
-        if !self.root_rect.contains(element_coords) {
+        self.insert_reporting(element).map(|_| ())
+    }
+
+    /// Inserts a point element, i.e. an element with a degenerate bounding box
+    /// of zero width and height.
+    ///
+    /// # Remarks
+    /// The element can be removed again using a [`QuadTreeElement`] with the
+    /// bounding box obtained from `AABB::from(point)`.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`point`] - The location of the element.
+    pub fn insert_point(&mut self, id: ElementId, point: Point) -> Result<(), InsertError> {
+        self.insert(QuadTreeElement::new(id, AABB::from(point)))
+    }
+
+    /// Inserts an element like [`insert()`] and reports how the insertion
+    /// affected the structure of the tree.
+    ///
+    /// # Arguments
+    /// * [`element`] - The element to insert.
+    pub fn insert_reporting(
+        &mut self,
+        element: QuadTreeElement<ElementId>,
+    ) -> Result<InsertOutcome, InsertError> {
+        self.insert_reporting_with(element, ALL_LAYERS, 0)
+    }
+
+    /// Inserts an element like [`insert()`] and assigns it to the layers whose
+    /// bits are set in the specified mask, e.g. for collision layers.
+    ///
+    /// # Remarks
+    /// Elements inserted through [`insert()`] belong to all layers. Use
+    /// [`intersect_aabb_masked()`] to only query elements of specific layers.
+    ///
+    /// # Arguments
+    /// * [`element`] - The element to insert.
+    /// * [`mask`] - The layer bits of the element.
+    pub fn insert_with_mask(
+        &mut self,
+        element: QuadTreeElement<ElementId>,
+        mask: u32,
+    ) -> Result<(), InsertError> {
+        self.insert_reporting_with(element, mask, 0).map(|_| ())
+    }
+
+    /// Inserts an element like [`insert()`] and assigns it the specified z-order,
+    /// e.g. the drawing order of overlapping UI elements.
+    ///
+    /// # Remarks
+    /// Elements inserted through [`insert()`] have a z-order of zero. Use
+    /// [`topmost_at_point()`] to find the element with the highest z-order at a point.
+    ///
+    /// # Arguments
+    /// * [`element`] - The element to insert.
+    /// * [`z`] - The z-order of the element; higher values are on top.
+    pub fn insert_with_z(
+        &mut self,
+        element: QuadTreeElement<ElementId>,
+        z: i32,
+    ) -> Result<(), InsertError> {
+        self.insert_reporting_with(element, ALL_LAYERS, z)
+            .map(|_| ())
+    }
+
+    /// Like [`insert_reporting()`] but assigns the element to the specified layers
+    /// and the specified z-order.
+    fn insert_reporting_with(
+        &mut self,
+        element: QuadTreeElement<ElementId>,
+        mask: u32,
+        z: i32,
+    ) -> Result<InsertOutcome, InsertError> {
+        if !self.root_rect.contains(&element.rect) {
             return Err(InsertError::OutOfBounds);
         }
 
-        let max_num_elements = self.max_num_elements;
+        // Replacing an element does not change the number of elements.
+        match self.duplicate_policy {
+            DuplicatePolicy::Allow => {}
+            DuplicatePolicy::Reject => {
+                if self.find_element_by_id(&element.id).is_some() {
+                    return Err(InsertError::DuplicateId);
+                }
+            }
+            DuplicatePolicy::Replace => {
+                if let Some(existing) = self.find_element_by_id(&element.id) {
+                    let (element_idx, outcome) = self.relocate(&existing, element.rect);
+                    let elem_data = unsafe { self.element_data.at_mut(element_idx) };
+                    elem_data.mask = mask;
+                    elem_data.z = z;
+                    return Ok(outcome);
+                }
+            }
+        }
 
+        if let Some(max_elements) = self.max_elements {
+            if self.len() >= max_elements {
+                return Err(InsertError::CapacityExceeded);
+            }
+        }
+
+        Ok(self.insert_unchecked(element, mask, z))
+    }
+
+    /// Inserts an element without checking it against the bounds or the capacity of the tree.
+    /// Use [`ALL_LAYERS`] as the `mask` to assign the element to all layers.
+    fn insert_unchecked(
+        &mut self,
+        element: QuadTreeElement<ElementId>,
+        mask: u32,
+        z: i32,
+    ) -> InsertOutcome {
         // Insert the actual element.
-        let element_idx = self.element_ids.insert(element.id);
-        let element_rect_idx = self.element_rects.insert(element.rect);
-        debug_assert_eq!(element_idx, element_rect_idx);
+        let element_idx = self.element_ids.insert(element.id.clone());
+        let element_data_idx = self.element_data.insert(ElementData {
+            rect: element.rect,
+            seq: self.next_seq,
+            mask,
+            z,
+        });
+        debug_assert_eq!(element_idx, element_data_idx);
+        self.element_slots
+            .entry(element.id)
+            .or_default()
+            .push(element_idx);
+        self.next_seq += 1;
+        self.generation += 1;
+
+        self.insert_element_index(element_idx, &element.rect)
+    }
+
+    /// Erases an element whose references were already removed from all leaves.
+    fn erase_element(&mut self, element_idx: free_list::IndexType) {
+        let id = unsafe { self.element_ids.at(element_idx) };
+        if let Some(slots) = self.element_slots.get_mut(id) {
+            slots.retain(|slot| *slot != element_idx);
+            if slots.is_empty() {
+                self.element_slots.remove(id);
+            }
+        }
+
+        self.element_ids.erase(element_idx);
+        self.element_data.erase(element_idx);
+        self.generation += 1;
+    }
+
+    /// Stores a reference to an already inserted element in the leaf
+    /// covering its bounding box, splitting nodes as needed.
+    ///
+    /// # Arguments
+    /// * [`element_idx`] - The index of the element.
+    /// * [`element_coords`] - The bounding box of the element.
+    fn insert_element_index(
+        &mut self,
+        element_idx: free_list::IndexType,
+        element_coords: &AABB,
+    ) -> InsertOutcome {
+        let max_num_elements = self.max_num_elements;
+        let mut outcome = InsertOutcome::default();
 
         let mut to_process: SmallVec<[NodeData; 128]> =
             smallvec::smallvec![self.get_root_node_data()];
@@ -143,17 +551,19 @@ where
                     let node = &mut self.nodes[leaf.index as usize];
                     node.first_child_or_element = element_node_idx;
                     node.element_count += 1;
+                    outcome.final_depth = leaf.depth;
                 } else {
                     // At this point we have to split the current node.
                     // We push the leaf back onto the stack in order to try to
                     // find a better insertion candidate from there.
                     self.distribute_elements_to_child_nodes(&leaf);
+                    outcome.splits_performed += 1;
                     to_process.push(leaf);
                 }
             }
         }
 
-        Ok(())
+        outcome
     }
 
     /// Splits the specified [`parent`] node into four and distributes its
@@ -171,7 +581,7 @@ where
         // For each element in the list ...
         while element_node_index != free_list::SENTINEL {
             let element_node = unsafe { *self.element_nodes.at(element_node_index) };
-            let element = unsafe { *self.element_rects.at(element_node.element_idx) };
+            let element = unsafe { self.element_data.at(element_node.element_idx) }.rect;
 
             self.assign_element_to_child_nodes(
                 mx,
@@ -269,13 +679,54 @@ where
     /// Removes the specified element.
     ///
     /// # Remarks
-    /// The element is located using its bounding box and identified using the ID.
+    /// The element is located using its bounding box and identified using the ID and
+    /// the bounding box, such that only one of multiple elements sharing an ID is removed.
     /// Because of that, the bounding box of the element must not change until is was
-    /// removed from the tree.
+    /// removed from the tree; see [`remove_by_id_scan()`] for a fallback.
+    ///
+    /// Every element is referenced by exactly one leaf: an element spanning multiple
+    /// quadrants of a branch is stored in the branch's "this" node rather than in each
+    /// of the quadrants. When a leaf splits, its elements are redistributed using the same
+    /// rule, and [`cleanup()`] only ever merges the leaves of a branch into the branch
+    /// itself. The path to the leaf holding an element is therefore fully determined by
+    /// its bounding box and the current shape of the tree, so that a single descent is
+    /// sufficient to find it.
     ///
     /// # Arguments
     /// * [`element`] - The element to remove.
     pub fn remove(&mut self, element: &QuadTreeElement<ElementId>) -> bool {
+        self.remove_with_fn(element, |_leaf| {})
+    }
+
+    /// Removes the specified element, like [`remove()`], and reports the bounding
+    /// boxes of the leaves it was removed from, e.g. to mark cells as dirty for rendering.
+    ///
+    /// # Remarks
+    /// Since every element is referenced by exactly one leaf, at most one box is reported.
+    /// For an element spanning multiple quadrants of a branch, this is the box of the branch.
+    ///
+    /// # Arguments
+    /// * [`element`] - The element to remove.
+    ///
+    /// # Returns
+    /// The boxes of the leaves, or `None` if the element was not found.
+    pub fn remove_reporting(
+        &mut self,
+        element: &QuadTreeElement<ElementId>,
+    ) -> Option<SmallVec<[AABB; 4]>> {
+        let mut cells = SmallVec::new();
+        if self.remove_with_fn(element, |leaf| cells.push(leaf.crect.get_aabb())) {
+            Some(cells)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the specified element, calling a function for each leaf it was removed from.
+    fn remove_with_fn<F>(&mut self, element: &QuadTreeElement<ElementId>, mut leaf_fn: F) -> bool
+    where
+        F: FnMut(&NodeData),
+    {
         // Find the leaves containing the node.
         let element_coords = &element.rect;
         let root = self.get_root_node_data();
@@ -289,7 +740,7 @@ where
         });
 
         // TODO: Execute in the callback?
-        while !leaves.is_empty() {
+        while found_element_idx == free_list::SENTINEL && !leaves.is_empty() {
             let leaf = leaves.pop_back();
             let leaf_node_data = self.nodes[leaf.index as usize];
 
@@ -298,158 +749,557 @@ where
                 continue;
             }
 
-            // Used for debug assertion.
-            let mut element_found = false;
-
             // Find the element in question.
             let mut element_node_idx = leaf_node_data.first_child_or_element;
-            let mut prev_element_node_idx = element_node_idx;
-            let mut new_first_child_or_element = element_node_idx;
+            let mut prev_element_node_idx = free_list::SENTINEL;
 
             while element_node_idx != free_list::SENTINEL {
                 let elem_node = *unsafe { self.element_nodes.at(element_node_idx) };
                 let elem_id = unsafe { self.element_ids.at(elem_node.element_idx) };
-
-                if *elem_id == element.id {
-                    debug_assert!(!element_found);
-                    element_found = true;
-
-                    // If the element to be deleted is the first element,
-                    // we need to update the leaf.
-                    if leaf_node_data.first_child_or_element == element_node_idx {
-                        new_first_child_or_element = elem_node.next;
-                    }
-
-                    // Update the previous node if it exists.
-                    if element_node_idx != prev_element_node_idx {
-                        unsafe { self.element_nodes.at_mut(prev_element_node_idx) }.next =
-                            elem_node.next;
-                    }
-
-                    // Remove the reference from this leaf and
-                    // keep track of the element index in the list.
-                    self.element_nodes.erase(element_node_idx);
-                    debug_assert!(
-                        found_element_idx == free_list::SENTINEL
-                            || found_element_idx == elem_node.element_idx
-                    );
-                    found_element_idx = elem_node.element_idx;
+                let elem_rect = &unsafe { self.element_data.at(elem_node.element_idx) }.rect;
+
+                // Duplicate IDs may be allowed, so the bounding box has to match as well.
+                // Of multiple identical elements, only the first one is removed.
+                if *elem_id != element.id || *elem_rect != element.rect {
+                    prev_element_node_idx = element_node_idx;
+                    element_node_idx = elem_node.next;
+                    continue;
                 }
 
-                prev_element_node_idx = element_node_idx;
-                element_node_idx = elem_node.next;
-
-                // We assume that a user never inserts the same element
-                // twice, therefore there is no need to visit the other
-                // elements of this node if we found the correct one.
-                //
-                // To assert that elements are only inserted once (per node),
-                // we allow further iteration during debugging.
-                #[cfg(not(debug_assertions))]
-                if element_found {
-                    break;
+                // Bypass the reference, updating either the leaf or the previous reference.
+                if prev_element_node_idx == free_list::SENTINEL {
+                    self.nodes[leaf.index as usize].first_child_or_element = elem_node.next;
+                } else {
+                    unsafe { self.element_nodes.at_mut(prev_element_node_idx) }.next =
+                        elem_node.next;
                 }
-            }
 
-            // Update the leaf node itself.
-            let node = &mut self.nodes[leaf.index as usize];
-            node.first_child_or_element = new_first_child_or_element;
+                // Remove the reference from this leaf and
+                // keep track of the element index in the list.
+                self.element_nodes.erase(element_node_idx);
+                found_element_idx = elem_node.element_idx;
 
-            // The user may try to remove an element that was not in the tree (anymore).
-            if element_found {
+                let node = &mut self.nodes[leaf.index as usize];
                 debug_assert!(node.element_count > 0);
                 node.element_count -= 1;
+                leaf_fn(&leaf);
+                break;
             }
         }
 
         if found_element_idx != free_list::SENTINEL {
-            self.element_ids.erase(found_element_idx);
-            self.element_rects.erase(found_element_idx);
+            self.erase_element(found_element_idx);
             true
         } else {
             false
         }
     }
 
-    // TODO: Prefer specialization, see https://github.com/rust-lang/rust/issues/31844
-    fn find_leaves_aabb_fn<F>(
-        &self,
-        root: NodeData,
-        rect: &AABB,
-        hint: FindLeafHint,
-        mut callback: F,
-    ) where
-        F: FnMut(&AABB, NodeData),
-    {
-        let mut to_process = NodeList::default();
-        to_process.push_back(root);
-
-        while to_process.len() > 0 {
-            let nd = to_process.pop_back();
-
-            // If this node is a leaf, insert it to the list.
-            if self.nodes[nd.index as usize].is_leaf() {
-                callback(rect, nd);
-                continue;
-            }
+    /// Removes all references to the element with the specified ID by scanning
+    /// every leaf of the tree, regardless of the element's bounding box.
+    ///
+    /// # Remarks
+    /// This is a fallback for when [`remove()`] fails because the bounding box
+    /// known to the caller no longer matches the one the element was inserted with.
+    /// It visits every element of the tree and should not be used as the regular way
+    /// of removing elements.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element to remove.
+    ///
+    /// # Returns
+    /// `true` if any reference to the element was found, `false` otherwise.
+    pub fn remove_by_id_scan(&mut self, id: &ElementId) -> bool {
+        let mut leaves = NodeList::default();
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| leaves.push_back(nd),
+        );
 
-            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+        let mut unlinked = Vec::new();
+        while !leaves.is_empty() {
+            let leaf = leaves.pop_back();
+            self.unlink_elements_from_leaf(
+                leaf.index,
+                |elem_id, _rect| elem_id == id,
+                |element_idx| unlinked.push(element_idx),
+            );
+        }
 
-            // Otherwise push the children that intersect the rectangle.
-            let quadrants = nd.crect.explore_quadrants_aabb(rect);
-            Self::collect_relevant_quadrants(&mut to_process, &nd, fc, quadrants, hint)
+        unlinked.sort_unstable();
+        unlinked.dedup();
+        for &element_idx in &unlinked {
+            self.erase_element(element_idx);
         }
+
+        !unlinked.is_empty()
     }
 
-    // TODO: Prefer specialization, see https://github.com/rust-lang/rust/issues/31844
-    fn find_leaves_generic_fn<T, F>(&self, root: NodeData, element: &T, mut callback: F)
-    where
-        T: IntersectsWith<AABB>,
-        F: FnMut(NodeData),
-    {
-        let mut to_process = NodeList::default();
-        to_process.push_back(root);
+    /// Creates a standalone copy of the part of the tree within the specified region.
+    ///
+    /// # Remarks
+    /// The new tree uses the same configuration as this tree. It contains all elements
+    /// intersecting the region, including the ones straddling its boundary;
+    /// these elements retain their full bounding box. The extents of the new tree
+    /// are the region grown to cover these elements, clamped to the extents of this
+    /// tree. The elements keep their relative insertion order.
+    ///
+    /// # Arguments
+    /// * [`region`] - The region to copy.
+    pub fn subtree(&self, region: &AABB) -> QuadTree<ElementId> {
+        let mut elements = Vec::new();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            region,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    if region.intersects_with(&elem_data.rect) {
+                        let element = QuadTreeElement::new(elem_id.clone(), elem_data.rect);
+                        elements.push((elem_data.seq, element, elem_data.mask, elem_data.z));
+                    }
+                });
+            },
+        );
 
-        while to_process.len() > 0 {
-            let nd = to_process.pop_back();
+        // Grow the region such that the centers of straddling elements lie within the new tree.
+        let root: AABB = self.root_rect.into();
+        let bounds = elements
+            .iter()
+            .fold(*region, |bounds, (_, element, _, _)| bounds + element.rect);
+        let left = bounds.tl.x.max(root.tl.x);
+        let top = bounds.tl.y.max(root.tl.y);
+        let right = bounds.br.x.min(root.br.x).max(left);
+        let bottom = bounds.br.y.min(root.br.y).max(top);
+
+        let mut tree = Self::with_capacity(
+            QuadRect::new(left, top, right - left, bottom - top),
+            self.max_depth,
+            self.max_num_elements,
+            self.smallest_cell_size,
+            elements.len(),
+            elements.len(),
+        );
+        tree.max_elements = self.max_elements;
+        tree.duplicate_policy = self.duplicate_policy;
 
-            // If this node is a leaf, insert it to the list.
-            if self.nodes[nd.index as usize].is_leaf() {
-                callback(nd);
-                continue;
-            }
+        // Keep the relative insertion order of the elements.
+        elements.sort_unstable_by_key(|(seq, _, _, _)| *seq);
 
-            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+        for (_, element, mask, z) in elements {
+            debug_assert!(tree.root_rect.contains(&element.rect));
+            tree.insert_unchecked(element, mask, z);
+        }
+        tree
+    }
 
-            // Otherwise push the children that intersect the rectangle.
-            let quadrants = nd.crect.explore_quadrants_generic(element);
-            Self::collect_relevant_quadrants(
-                &mut to_process,
-                &nd,
-                fc,
-                quadrants,
-                FindLeafHint::Query,
-            )
+    /// Gets the entry of the element with the specified ID for in-place manipulation.
+    ///
+    /// # Remarks
+    /// By default, the tree accepts the same ID multiple times; see [`set_duplicate_policy()`].
+    /// Inserting through a [`VacantEntry`] guards against accidental double inserts.
+    /// If multiple elements share the ID, the entry refers to the one inserted first.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`rect`] - The bounding box to insert the element with if it does not exist yet.
+    pub fn entry(&mut self, id: ElementId, rect: AABB) -> Entry<'_, ElementId> {
+        match self.find_element_by_id(&id) {
+            Some(element) => Entry::Occupied(OccupiedEntry::new(self, element)),
+            None => Entry::Vacant(VacantEntry::new(self, QuadTreeElement::new(id, rect))),
         }
     }
 
-    /// Visits all leaf nodes in the tree, passing the node information to the provided closure.
-    pub fn visit_leaves<F>(&self, mut visit: F)
-    where
-        F: FnMut(NodeInfo),
-    {
-        let mut to_process = NodeList::default();
+    /// Moves the element with the specified ID to the specified bounding box,
+    /// or inserts it if no element with this ID is stored yet, e.g. to update
+    /// or add an entity in a game loop.
+    ///
+    /// # Remarks
+    /// A moved element keeps its layers, its z-order and its position in the
+    /// insertion order. If the new bounding box is out of bounds, the tree is
+    /// left unchanged. The element is looked up by its ID without visiting the tree.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`new_rect`] - The new bounding box of the element.
+    pub fn upsert(&mut self, id: ElementId, new_rect: AABB) -> Result<(), InsertError> {
+        if !self.root_rect.contains(&new_rect) {
+            return Err(InsertError::OutOfBounds);
+        }
+
+        match self.find_element_by_id(&id) {
+            Some(element) => {
+                self.relocate(&element, new_rect);
+                Ok(())
+            }
+            None => self.insert(QuadTreeElement::new(id, new_rect)),
+        }
+    }
+
+    /// Moves a stored element to a new bounding box within the bounds of the tree,
+    /// keeping its slot and with it all of its attributes.
+    ///
+    /// # Returns
+    /// The slot of the element and the outcome of storing it in its new leaf.
+    fn relocate(
+        &mut self,
+        element: &QuadTreeElement<ElementId>,
+        new_rect: AABB,
+    ) -> (free_list::IndexType, InsertOutcome) {
+        debug_assert!(self.root_rect.contains(&new_rect));
+
+        let mut leaves = NodeList::default();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &element.rect,
+            FindLeafHint::Mutate,
+            |_rect, nd| leaves.push_back(nd),
+        );
+
+        // Only unlink a single element, as duplicate IDs may be allowed.
+        let mut unlinked = None;
+        while unlinked.is_none() && !leaves.is_empty() {
+            let leaf = leaves.pop_back();
+            let mut remaining = 1;
+            self.unlink_elements_from_leaf(
+                leaf.index,
+                |elem_id, elem_rect| {
+                    let matches =
+                        remaining > 0 && *elem_id == element.id && *elem_rect == element.rect;
+                    if matches {
+                        remaining -= 1;
+                    }
+                    matches
+                },
+                |element_idx| unlinked = Some(element_idx),
+            );
+        }
+
+        let element_idx = unlinked.expect("the element should be stored in the tree");
+        unsafe { self.element_data.at_mut(element_idx) }.rect = new_rect;
+        let outcome = self.insert_element_index(element_idx, &new_rect);
+        self.generation += 1;
+        (element_idx, outcome)
+    }
+
+    /// Finds the first inserted element with the specified ID.
+    fn find_element_by_id(&self, id: &ElementId) -> Option<QuadTreeElement<ElementId>> {
+        let element_idx = *self.element_slots.get(id)?.first()?;
+        let elem_rect = unsafe { self.element_data.at(element_idx) }.rect;
+        Some(QuadTreeElement::new(id.clone(), elem_rect))
+    }
+
+    /// Returns the IDs that are stored for more than one element, each reported once.
+    ///
+    /// # Remarks
+    /// Unless the tree's [`duplicate_policy()`] prevents it, inserting an ID twice
+    /// creates two independent elements, both of which are reported by queries.
+    /// This method visits all elements of the tree and is meant for diagnostics.
+    pub fn find_duplicate_ids(&self) -> Vec<ElementId> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, _| {
+                    if !seen.insert(elem_id.clone()) && !duplicates.contains(elem_id) {
+                        duplicates.push(elem_id.clone());
+                    }
+                });
+            },
+        );
+        duplicates
+    }
+
+    /// Removes all elements whose bounding box intersects the specified one
+    /// and returns them.
+    ///
+    /// # Remarks
+    /// This combines [`intersect_aabb()`] and [`remove()`] in a single traversal.
+    /// Since every element is stored in exactly one leaf, each removed element
+    /// is returned exactly once.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn take_in_aabb(&mut self, rect: &AABB) -> Vec<QuadTreeElement<ElementId>> {
+        let mut leaves = NodeList::default();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |_rect, nd| leaves.push_back(nd),
+        );
+
+        let mut taken = Vec::new();
+        while !leaves.is_empty() {
+            let leaf = leaves.pop_back();
+            self.unlink_elements_from_leaf(
+                leaf.index,
+                |_id, elem_rect| rect.intersects_with(elem_rect),
+                |element_idx| taken.push(element_idx),
+            );
+        }
+
+        taken
+            .into_iter()
+            .map(|element_idx| {
+                let id = unsafe { self.element_ids.at(element_idx) }.clone();
+                let elem_rect = unsafe { self.element_data.at(element_idx) }.rect;
+                self.erase_element(element_idx);
+                QuadTreeElement::new(id, elem_rect)
+            })
+            .collect()
+    }
+
+    /// Calls a function for each element whose bounding box intersects the specified
+    /// one, allowing the function to modify the element's bounding box in place.
+    /// Afterwards, each visited element is stored in the leaf covering its new bounding box.
+    ///
+    /// # Remarks
+    /// Elements whose new bounding box is no longer contained in the tree's root
+    /// are removed from the tree and returned. Since every element is stored in
+    /// exactly one leaf, the function is called exactly once for each element.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    /// * [`update_fn`] - The function called for each element's ID and bounding box.
+    pub fn for_each_in_aabb_mut_rect<F>(
+        &mut self,
+        rect: &AABB,
+        mut update_fn: F,
+    ) -> Vec<QuadTreeElement<ElementId>>
+    where
+        F: FnMut(ElementId, &mut AABB),
+    {
+        let mut leaves = NodeList::default();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |_rect, nd| leaves.push_back(nd),
+        );
+
+        // Unlink all matches first so that splits caused by re-placing
+        // elements do not interfere with the traversal.
+        let mut matched = Vec::new();
+        while !leaves.is_empty() {
+            let leaf = leaves.pop_back();
+            self.unlink_elements_from_leaf(
+                leaf.index,
+                |_id, elem_rect| rect.intersects_with(elem_rect),
+                |element_idx| matched.push(element_idx),
+            );
+        }
+
+        if !matched.is_empty() {
+            self.generation += 1;
+        }
+
+        let mut removed = Vec::new();
+        for element_idx in matched {
+            let id = unsafe { self.element_ids.at(element_idx) }.clone();
+            let elem_rect = &mut unsafe { self.element_data.at_mut(element_idx) }.rect;
+            update_fn(id.clone(), elem_rect);
+            let elem_rect = *elem_rect;
+
+            if self.root_rect.contains(&elem_rect) {
+                self.insert_element_index(element_idx, &elem_rect);
+            } else {
+                self.erase_element(element_idx);
+                removed.push(QuadTreeElement::new(id, elem_rect));
+            }
+        }
+
+        removed
+    }
+
+    /// Removes the references to all elements of a leaf that match the predicate
+    /// and passes the index of each unlinked element to the provided closure.
+    ///
+    /// # Remarks
+    /// The elements themselves are not erased; this is left to the caller.
+    fn unlink_elements_from_leaf<P, F>(
+        &mut self,
+        leaf_index: u32,
+        mut predicate: P,
+        mut unlinked: F,
+    ) where
+        P: FnMut(&ElementId, &AABB) -> bool,
+        F: FnMut(free_list::IndexType),
+    {
+        debug_assert!(self.nodes[leaf_index as usize].is_leaf());
+
+        let mut prev_element_node_idx = free_list::SENTINEL;
+        let mut element_node_idx = self.nodes[leaf_index as usize].first_child_or_element;
+
+        while element_node_idx != free_list::SENTINEL {
+            let elem_node = *unsafe { self.element_nodes.at(element_node_idx) };
+            let elem_id = unsafe { self.element_ids.at(elem_node.element_idx) };
+            let elem_rect = &unsafe { self.element_data.at(elem_node.element_idx) }.rect;
+
+            if !predicate(elem_id, elem_rect) {
+                prev_element_node_idx = element_node_idx;
+                element_node_idx = elem_node.next;
+                continue;
+            }
+
+            // Bypass the reference, updating either the leaf or the previous reference.
+            if prev_element_node_idx == free_list::SENTINEL {
+                self.nodes[leaf_index as usize].first_child_or_element = elem_node.next;
+            } else {
+                unsafe { self.element_nodes.at_mut(prev_element_node_idx) }.next = elem_node.next;
+            }
+
+            let node = &mut self.nodes[leaf_index as usize];
+            debug_assert!(node.element_count > 0);
+            node.element_count -= 1;
+
+            self.element_nodes.erase(element_node_idx);
+            unlinked(elem_node.element_idx);
+            element_node_idx = elem_node.next;
+        }
+    }
+
+    // TODO: Prefer specialization, see https://github.com/rust-lang/rust/issues/31844
+    fn find_leaves_aabb_fn<F>(&self, root: NodeData, rect: &AABB, hint: FindLeafHint, callback: F)
+    where
+        F: FnMut(&AABB, NodeData),
+    {
+        let mut to_process = NodeList::default();
+        self.find_leaves_aabb_with_fn(root, rect, hint, &mut to_process, callback);
+    }
+
+    /// Like [`find_leaves_aabb_fn()`] but uses the provided list as the traversal stack.
+    /// The list is expected to be empty and will be empty after the call.
+    fn find_leaves_aabb_with_fn<F>(
+        &self,
+        root: NodeData,
+        rect: &AABB,
+        hint: FindLeafHint,
+        to_process: &mut NodeList,
+        mut callback: F,
+    ) where
+        F: FnMut(&AABB, NodeData),
+    {
+        debug_assert!(to_process.is_empty());
+        to_process.push_back(root);
+
+        while to_process.len() > 0 {
+            let nd = to_process.pop_back();
+
+            // If this node is a leaf, insert it to the list.
+            if self.nodes[nd.index as usize].is_leaf() {
+                callback(rect, nd);
+                continue;
+            }
+
+            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+
+            // Otherwise push the children that intersect the rectangle.
+            let quadrants = nd.crect.explore_quadrants_aabb(rect);
+            Self::collect_relevant_quadrants(to_process, &nd, fc, quadrants, hint)
+        }
+    }
+
+    /// Pops nodes from the traversal stack until a leaf is found, pushing the children
+    /// of branches intersecting the specified rectangle. Used for lazy queries.
+    pub(crate) fn next_leaf_aabb(
+        &self,
+        rect: &AABB,
+        to_process: &mut NodeList,
+    ) -> Option<NodeData> {
+        while !to_process.is_empty() {
+            let nd = to_process.pop_back();
+            if self.nodes[nd.index as usize].is_leaf() {
+                return Some(nd);
+            }
+
+            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+            let quadrants = nd.crect.explore_quadrants_aabb(rect);
+            Self::collect_relevant_quadrants(to_process, &nd, fc, quadrants, FindLeafHint::Query)
+        }
+        None
+    }
+
+    /// Gets the node described by the specified node data.
+    #[inline]
+    pub(crate) fn node_of(&self, nd: &NodeData) -> Node {
+        self.nodes[nd.index as usize]
+    }
+
+    /// Gets the index of the first element node of the specified leaf.
+    #[inline]
+    pub(crate) fn first_element_node_of(&self, leaf: &NodeData) -> free_list::IndexType {
+        let node = &self.nodes[leaf.index as usize];
+        debug_assert!(node.is_leaf());
+        node.first_child_or_element
+    }
+
+    /// Gets the ID and bounding box of the element referenced by the specified element node,
+    /// as well as the index of the next element node in the leaf.
+    #[inline]
+    pub(crate) fn element_node_at(
+        &self,
+        elem_node_idx: free_list::IndexType,
+    ) -> (ElementId, AABB, free_list::IndexType) {
+        let elem_node = unsafe { self.element_nodes.at(elem_node_idx) };
+        let elem_id = unsafe { self.element_ids.at(elem_node.element_idx) }.clone();
+        let elem_rect = unsafe { self.element_data.at(elem_node.element_idx) }.rect;
+        (elem_id, elem_rect, elem_node.next)
+    }
+
+    // TODO: Prefer specialization, see https://github.com/rust-lang/rust/issues/31844
+    fn find_leaves_generic_fn<T, F>(&self, root: NodeData, element: &T, mut callback: F)
+    where
+        T: IntersectsWith<AABB>,
+        F: FnMut(NodeData),
+    {
+        let mut to_process = NodeList::default();
+        to_process.push_back(root);
+
+        while to_process.len() > 0 {
+            let nd = to_process.pop_back();
+
+            // If this node is a leaf, insert it to the list.
+            if self.nodes[nd.index as usize].is_leaf() {
+                callback(nd);
+                continue;
+            }
+
+            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+
+            // Otherwise push the children that intersect the rectangle.
+            let quadrants = nd.crect.explore_quadrants_generic(element);
+            Self::collect_relevant_quadrants(
+                &mut to_process,
+                &nd,
+                fc,
+                quadrants,
+                FindLeafHint::Query,
+            )
+        }
+    }
+
+    /// Visits all leaf nodes in the tree, passing the node information to the provided closure.
+    pub fn visit_leaves<F>(&self, mut visit: F)
+    where
+        F: FnMut(NodeInfo),
+    {
+        let mut to_process = NodeList::default();
         to_process.push_back(self.get_root_node_data());
 
         while to_process.len() > 0 {
             let nd = to_process.pop_back();
 
-            // If the index is divisible by 5, this node is referring to the
-            // parent itself. We skip enumerating this one since the proper child
-            // nodes will be enumerated individually.
-            // We subtract one to account for the root node.
-            let is_this_node = nd.index > 0 && ((nd.index - 1) % 5) == 0;
-            if is_this_node {
+            // We skip enumerating the node referring to the parent itself
+            // since the proper child nodes will be enumerated individually.
+            if Self::is_this_node(&nd) {
                 debug_assert!(self.nodes[nd.index as usize].is_leaf());
                 continue;
             }
@@ -471,6 +1321,117 @@ where
         }
     }
 
+    /// Visits all leaf nodes in the tree in Z-order, passing the node information
+    /// to the provided closure.
+    ///
+    /// # Remarks
+    /// Unlike [`visit_leaves()`], the order of the leaves does not depend on the
+    /// layout of the nodes in memory. Leaves are ordered by the [`morton_code()`]
+    /// of their centers, which visits neighboring leaves close to each other,
+    /// e.g. for serialization or tiled rendering.
+    ///
+    /// [`morton_code()`]: crate::quadtree::morton_code
+    pub fn visit_leaves_morton<F>(&self, mut visit: F)
+    where
+        F: FnMut(NodeInfo),
+    {
+        let mut leaves = Vec::new();
+        self.visit_leaves(|leaf| {
+            let code = morton_code(leaf.nd.crect.center_x, leaf.nd.crect.center_y);
+            leaves.push((code, leaf));
+        });
+
+        leaves.sort_by_key(|(code, _)| *code);
+        for (_, leaf) in leaves {
+            visit(leaf);
+        }
+    }
+
+    /// Visits all leaf nodes in the tree, passing the node information and the
+    /// information of all leaves sharing an edge with it to the provided closure.
+    ///
+    /// # Remarks
+    /// Like [`visit_leaves()`], this skips the nodes storing the elements that span
+    /// multiple quadrants, since these overlap with the leaves of their parent.
+    /// Leaves touching only at a corner are not considered adjacent.
+    pub fn visit_leaf_neighbors<F>(&self, mut visit: F)
+    where
+        F: FnMut(NodeInfo, &[NodeInfo]),
+    {
+        let mut leaves = Vec::new();
+        self.visit_leaves(|leaf| leaves.push(leaf));
+
+        let mut to_process = NodeList::default();
+        let mut neighbors = Vec::new();
+        for leaf in leaves {
+            let crect = leaf.nd.crect;
+
+            // Grow the rectangle by one unit in order to explore the quadrants
+            // on the other side of the parent's center lines as well.
+            let rect = AABB::new(
+                crect.left().saturating_sub(1),
+                crect.top().saturating_sub(1),
+                crect.right().saturating_add(1),
+                crect.bottom().saturating_add(1),
+            );
+
+            neighbors.clear();
+            self.find_leaves_aabb_with_fn(
+                self.get_root_node_data(),
+                &rect,
+                FindLeafHint::Query,
+                &mut to_process,
+                |_, nd| {
+                    if nd.index == leaf.nd.index || Self::is_this_node(&nd) {
+                        return;
+                    }
+                    if crect.shares_edge_with(&nd.crect) {
+                        let element_count = self.nodes[nd.index as usize].element_count;
+                        neighbors.push(NodeInfo::from(nd, element_count));
+                    }
+                },
+            );
+
+            visit(leaf, &neighbors);
+        }
+    }
+
+    /// Determines whether the node refers to the "this" node of its parent, i.e. the
+    /// node storing the elements that span multiple quadrants.
+    #[inline]
+    fn is_this_node(nd: &NodeData) -> bool {
+        // Every fifth node is a "this" node, offset by one to account for the root node.
+        nd.index % 5 == 1
+    }
+
+    /// Returns the bounding box and the element IDs of every non-empty leaf.
+    ///
+    /// # Remarks
+    /// Unlike [`visit_leaves()`], this includes the leaves storing the elements that span
+    /// multiple quadrants of a branch; these leaves report the bounding box of the branch.
+    /// Since every element is stored in exactly one leaf, every ID is reported once.
+    pub fn leaves_with_elements(&self) -> Vec<(AABB, Vec<ElementId>)> {
+        let mut leaves = Vec::new();
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                let node = &self.nodes[nd.index as usize];
+                if node.is_empty() {
+                    return;
+                }
+
+                let mut ids = Vec::with_capacity(node.element_count as usize);
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, _| ids.push(elem_id.clone()));
+
+                leaves.push((nd.crect.get_aabb(), ids));
+            },
+        );
+        leaves
+    }
+
     /// Collects the relevant quadrant nodes.
     #[inline]
     fn collect_relevant_quadrants(
@@ -548,152 +1509,1126 @@ where
             }
         }
 
-        // In intersection tests we always need to explore the self node.
-        to_process.push_back(NodeData::new(
-            split_quadrants[0],
-            first_child_id + 0,
-            // The "this" node is at the same depth and cannot split.
-            depth,
-            false,
-        ));
+        // In intersection tests we always need to explore the self node.
+        to_process.push_back(NodeData::new(
+            split_quadrants[0],
+            first_child_id + 0,
+            // The "this" node is at the same depth and cannot split.
+            depth,
+            false,
+        ));
+    }
+
+    /// Subdivides all leaves of the tree down to the specified depth, such that a burst
+    /// of evenly distributed inserts does not need to split nodes incrementally.
+    ///
+    /// # Remarks
+    /// Nodes are only split as long as the tree's maximum depth and smallest cell size
+    /// allow it. Elements already stored in the tree are distributed onto the new leaves.
+    /// Since [`cleanup()`] collapses sparse branches, it undoes the split of leaves
+    /// that were not populated in the meantime.
+    ///
+    /// # Arguments
+    /// * [`depth`] - The depth to subdivide the leaves to.
+    pub fn pre_split_to_depth(&mut self, depth: u8) {
+        let mut to_process = vec![self.get_root_node_data()];
+        while let Some(nd) = to_process.pop() {
+            if nd.depth >= depth {
+                continue;
+            }
+
+            if self.nodes[nd.index as usize].is_leaf() {
+                if !nd.can_split_further(self.smallest_cell_size, self.max_depth) {
+                    continue;
+                }
+                self.distribute_elements_to_child_nodes(&nd);
+            }
+
+            // The "this" node at offset 0 cannot be split.
+            let fc = self.nodes[nd.index as usize].get_first_child_node_index();
+            let split_quadrants = nd.crect.split_quadrants();
+            for (offset, crect) in split_quadrants.iter().enumerate().skip(1) {
+                to_process.push(NodeData::new(
+                    *crect,
+                    fc + offset as u32,
+                    nd.depth + 1,
+                    true,
+                ));
+            }
+        }
+    }
+
+    /// Releases the memory of unused nodes and elements at the end of the internal
+    /// lists, e.g. in order to reclaim memory after a load spike.
+    ///
+    /// # Remarks
+    /// Call [`cleanup()`] first in order to collapse the branches that are no longer
+    /// needed. Since the indices of the remaining nodes and elements must stay valid,
+    /// unused entries followed by used ones are only reused by later inserts;
+    /// use [`rebuild()`] to remove these holes as well.
+    pub fn shrink_to_fit(&mut self) {
+        // Free node blocks are chained through their first node.
+        let mut is_free = vec![false; self.nodes.len()];
+        let mut free_node = self.free_node;
+        while free_node != free_list::SENTINEL {
+            is_free[free_node as usize] = true;
+            free_node = self.nodes[free_node as usize].first_child_or_element;
+        }
+
+        // The root is never free; all other nodes come in blocks of five.
+        let mut len = self.nodes.len();
+        while len > 1 && is_free[len - 5] {
+            len -= 5;
+        }
+        self.nodes.truncate(len);
+        self.nodes.shrink_to_fit();
+
+        self.free_node = free_list::SENTINEL;
+        for first_child_index in (1..len).step_by(5).rev() {
+            if is_free[first_child_index] {
+                self.nodes[first_child_index].first_child_or_element = self.free_node;
+                self.free_node = first_child_index as NodeIndexType;
+            }
+        }
+
+        self.element_ids.shrink_to_fit();
+        self.element_data.shrink_to_fit();
+        self.element_slots.shrink_to_fit();
+        self.element_nodes.shrink_to_fit();
+    }
+
+    /// Reorders the element lists of all leaves such that queries access the stored
+    /// elements in ascending memory order, improving the cache behavior of queries
+    /// on trees that no longer change.
+    ///
+    /// # Remarks
+    /// Only the links between the element references of each leaf are rewritten;
+    /// no element is moved. Inserts prepend to the lists of the leaves, such that
+    /// subsequent modifications gradually undo the ordering. Unlike [`rebuild()`],
+    /// this does not remove holes from the internal lists.
+    pub fn sort_leaf_elements(&mut self) {
+        let mut leaves = Vec::new();
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| leaves.push(nd.index),
+        );
+
+        let mut chain = Vec::new();
+        for leaf_index in leaves {
+            chain.clear();
+            let mut elem_node_idx = self.nodes[leaf_index as usize].first_child_or_element;
+            while elem_node_idx != free_list::SENTINEL {
+                let elem_node = unsafe { self.element_nodes.at(elem_node_idx) };
+                chain.push((elem_node.element_idx, elem_node_idx));
+                elem_node_idx = elem_node.next;
+            }
+
+            // Relink from the back such that the list starts with the lowest element index.
+            chain.sort_unstable();
+            let mut next = free_list::SENTINEL;
+            for &(_, elem_node_idx) in chain.iter().rev() {
+                unsafe { self.element_nodes.at_mut(elem_node_idx) }.next = next;
+                next = elem_node_idx;
+            }
+            self.nodes[leaf_index as usize].first_child_or_element = next;
+        }
+    }
+
+    /// Removes all elements from the tree and inserts them again, producing a compact
+    /// node layout without holes in the internal lists. The IDs and bounding boxes
+    /// of the elements are preserved, as is their insertion order.
+    ///
+    /// # Remarks
+    /// After many inserts and removals, elements and nodes are scattered in memory,
+    /// which slows down queries. Call this method periodically to restore locality.
+    /// The memory allocated by the tree is reused.
+    pub fn rebuild(&mut self) {
+        let mut elements = Vec::with_capacity(self.len());
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    let element = QuadTreeElement::new(elem_id.clone(), elem_data.rect);
+                    elements.push((elem_data.seq, element, elem_data.mask, elem_data.z));
+                });
+            },
+        );
+
+        self.element_ids.clear();
+        self.element_data.clear();
+        self.element_slots.clear();
+        self.element_nodes.clear();
+        self.nodes.clear();
+        self.nodes.push(Node::default());
+        self.free_node = free_list::SENTINEL;
+
+        elements.sort_unstable_by_key(|(seq, _, _, _)| *seq);
+        for (_, element, mask, z) in elements {
+            self.insert_unchecked(element, mask, z);
+        }
+    }
+
+    /// Prunes unused child nodes from the tree.
+    ///
+    /// # Remarks
+    /// The tree is never pruned automatically for performance reasons. Call
+    /// this method after all elements were removed or updated.
+    ///
+    /// Branches whose children are all leaves are collapsed into a single leaf if
+    /// the children hold no more than `max_num_elements` elements in total. The
+    /// elements of the children are then moved to the former branch. Since branches
+    /// are processed bottom-up, collapsing may cascade up to the root.
+    ///
+    /// # Returns
+    /// `true` if any branch was collapsed, `false` otherwise.
+    pub fn cleanup(&mut self) -> bool {
+        // Only process the root if it is not a leaf.
+        if self.nodes[0].is_leaf() {
+            return false;
+        }
+
+        // Collect all branches in pre-order, such that processing them in reverse
+        // order visits the children of each branch before the branch itself.
+        // TODO: revisit the small list size, check element count
+        let mut branches: SmallVec<[NodeIndexType; 128]> = SmallVec::new();
+        let mut to_process: SmallVec<[NodeIndexType; 128]> = smallvec::smallvec![0];
+        while let Some(node_index) = to_process.pop() {
+            branches.push(node_index);
+            let first_child_index = self.nodes[node_index as usize].get_first_child_node_index();
+            for j in 0..5 {
+                let child_index = first_child_index + j;
+                if self.nodes[child_index as usize].is_branch() {
+                    to_process.push(child_index);
+                }
+            }
+        }
+
+        let mut tree_compacted = false;
+        for &node_index in branches.iter().rev() {
+            let first_child_index = self.nodes[node_index as usize].get_first_child_node_index();
+            let children = first_child_index as usize..(first_child_index + 5) as usize;
+
+            // Only branches consisting of leaves only can be collapsed, and only
+            // if their elements would not immediately cause a split again.
+            let children_are_leaves = self.nodes[children.clone()]
+                .iter()
+                .all(|child| child.is_leaf());
+            if !children_are_leaves {
+                continue;
+            }
+
+            let num_elements: u32 = self.nodes[children.clone()]
+                .iter()
+                .map(|child| child.element_count)
+                .sum();
+            if num_elements > self.max_num_elements {
+                continue;
+            }
+
+            // Chain the element lists of all children together.
+            let mut first_element = free_list::SENTINEL;
+            for child_index in children.clone() {
+                let child = self.nodes[child_index];
+                if child.is_empty() {
+                    continue;
+                }
+
+                let mut last_element = child.get_first_element_node_index();
+                loop {
+                    let next = unsafe { self.element_nodes.at(last_element) }.next;
+                    if next == free_list::SENTINEL {
+                        break;
+                    }
+                    last_element = next;
+                }
+
+                unsafe { self.element_nodes.at_mut(last_element) }.next = first_element;
+                first_element = child.get_first_element_node_index();
+            }
+
+            // Push all 5 children to the free list. Only the first child is reset when
+            // the block is reused, so the others must be left as empty leaves.
+            for child in &mut self.nodes[children] {
+                child.make_empty_leaf();
+            }
+            self.nodes[first_child_index as usize].first_child_or_element = self.free_node;
+            self.free_node = first_child_index;
+
+            // Make this node the new leaf.
+            let node = &mut self.nodes[node_index as usize];
+            node.make_empty_leaf();
+            node.first_child_or_element = first_element;
+            node.element_count = num_elements;
+
+            tree_compacted = true;
+        }
+
+        if tree_compacted {
+            self.generation += 1;
+        }
+
+        tree_compacted
+    }
+
+    /// Counts the total number of element references stored in the leaves of the tree.
+    ///
+    /// # Remarks
+    /// Since every element is referenced by exactly one leaf, this equals the
+    /// number of elements. Use it to size the `reference_capacity` of [`with_capacity()`].
+    pub fn references(&self) -> usize {
+        self.count_element_references()
+    }
+
+    /// Counts the total number of references. This number should be at least
+    /// the number of elements inserted; it will be higher if elements
+    /// span multiple cells.
+    #[allow(dead_code)]
+    pub(crate) fn count_element_references(&self) -> usize {
+        let mut to_process: SmallVec<[usize; 128]> = smallvec::smallvec![0];
+        let mut count = 0usize;
+        while !to_process.is_empty() {
+            let index = to_process.pop().unwrap();
+            let node = &self.nodes[index];
+            if node.is_branch() {
+                for j in 0..5 {
+                    to_process.push((node.first_child_or_element + j) as usize);
+                }
+            } else {
+                count += node.element_count as usize;
+            }
+        }
+
+        debug_assert!(count >= self.element_ids.len());
+        debug_assert!(count >= self.element_data.len());
+        count
+    }
+
+    /// Determines the largest number of elements stored in a single leaf.
+    ///
+    /// # Remarks
+    /// Leaves that cannot be split further (e.g. because they reached the maximum
+    /// depth or the smallest cell size) keep accepting elements beyond the configured
+    /// maximum number of elements. Since queries test every element of a visited leaf,
+    /// a value far exceeding that maximum indicates a degenerate tree configuration.
+    pub fn longest_leaf_chain(&self) -> usize {
+        let mut to_process: SmallVec<[usize; 128]> = smallvec::smallvec![0];
+        let mut longest = 0usize;
+        while let Some(index) = to_process.pop() {
+            let node = &self.nodes[index];
+            if node.is_branch() {
+                for j in 0..5 {
+                    to_process.push((node.first_child_or_element + j) as usize);
+                }
+            } else {
+                longest = longest.max(node.element_count as usize);
+            }
+        }
+        longest
+    }
+
+    /// Checks the structural invariants of the tree and reports the first violation found.
+    ///
+    /// # Remarks
+    /// This is a debugging aid, e.g. for detecting corruption after complex sequences
+    /// of removals and cleanups. It visits every node and element reference of the tree.
+    ///
+    /// The following invariants are checked:
+    /// - The children of every branch form a contiguous block of five nodes within the node list,
+    ///   which is not shared with any other branch or with the free node list.
+    /// - The element count of every leaf matches the length of its element chain.
+    /// - Every element reference points to a valid element node, which in turn points to a valid element.
+    /// - The free node list is well-formed, i.e. in bounds and without cycles.
+    /// - The lookup of elements by ID covers every element exactly once.
+    pub fn validate(&self) -> Result<(), String> {
+        let num_nodes = self.nodes.len();
+        let num_element_nodes = self.element_nodes.capacity();
+        let num_elements = self.element_ids.capacity();
+
+        // Tracks the child blocks already seen, either in the tree or in the free list.
+        let mut seen_blocks = HashSet::new();
+        let mut check_block = |first_child: free_list::IndexType| -> Result<(), String> {
+            let first = first_child as usize;
+            if first % 5 != 1 || first + 4 >= num_nodes {
+                return Err(format!(
+                    "child block at {} is misaligned or out of bounds ({} nodes)",
+                    first_child, num_nodes
+                ));
+            }
+            if !seen_blocks.insert(first_child) {
+                return Err(format!(
+                    "child block at {} is referenced twice",
+                    first_child
+                ));
+            }
+            Ok(())
+        };
+
+        let mut to_process: SmallVec<[NodeIndexType; 128]> = smallvec::smallvec![0];
+        while let Some(index) = to_process.pop() {
+            let node = &self.nodes[index as usize];
+            if node.is_branch() {
+                let first_child = node.first_child_or_element;
+                check_block(first_child).map_err(|e| format!("branch {}: {}", index, e))?;
+                to_process.extend((0..5).map(|j| first_child + j));
+                continue;
+            }
+
+            let mut chain_length = 0u32;
+            let mut elem_node_idx = node.first_child_or_element;
+            while elem_node_idx != free_list::SENTINEL {
+                if elem_node_idx as usize >= num_element_nodes {
+                    return Err(format!(
+                        "leaf {}: element node index {} is out of bounds ({} element nodes)",
+                        index, elem_node_idx, num_element_nodes
+                    ));
+                }
+                chain_length += 1;
+                if chain_length > node.element_count {
+                    return Err(format!(
+                        "leaf {}: element chain is longer than the element count of {}",
+                        index, node.element_count
+                    ));
+                }
+
+                let elem_node = unsafe { self.element_nodes.at(elem_node_idx) };
+                if elem_node.element_idx as usize >= num_elements {
+                    return Err(format!(
+                        "leaf {}: element index {} is out of bounds ({} elements)",
+                        index, elem_node.element_idx, num_elements
+                    ));
+                }
+                elem_node_idx = elem_node.next;
+            }
+
+            if chain_length != node.element_count {
+                return Err(format!(
+                    "leaf {}: element count is {} but the element chain has length {}",
+                    index, node.element_count, chain_length
+                ));
+            }
+        }
+
+        // Freed blocks are only linked through their first node. Since every block
+        // is checked to be unique, cycles are detected as well.
+        let mut free_node = self.free_node;
+        while free_node != free_list::SENTINEL {
+            check_block(free_node).map_err(|e| format!("free node list: {}", e))?;
+            free_node = self.nodes[free_node as usize].first_child_or_element;
+        }
+
+        let mut num_slots = 0;
+        for (id, slots) in &self.element_slots {
+            for &element_idx in slots {
+                if element_idx as usize >= num_elements
+                    || unsafe { self.element_ids.at(element_idx) } != id
+                {
+                    return Err(format!(
+                        "ID lookup: element index {} does not store the looked up ID",
+                        element_idx
+                    ));
+                }
+            }
+            num_slots += slots.len();
+        }
+        if num_slots != self.len() {
+            return Err(format!(
+                "ID lookup: {} element indexes are stored for {} elements",
+                num_slots,
+                self.len()
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn get_root_node_data(&self) -> NodeData {
+        NodeData::new_from_root(&self.root_rect, true)
+    }
+
+    /// Returns the set of IDs that occupy space within the
+    /// specified bounding box.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    #[inline]
+    pub fn intersect_aabb(&self, rect: &AABB) -> Vec<ElementId> {
+        let root = self.get_root_node_data();
+        let mut node_set = Vec::with_capacity(128);
+
+        self.find_leaves_aabb_fn(root, rect, FindLeafHint::Query, |rect, nd| {
+            self.intersect_from_leaf(rect, nd, &mut |id| {
+                node_set.push(id);
+            });
+        });
+
+        node_set
+    }
+
+    /// Returns the IDs of all elements that occupy space within the specified
+    /// bounding box and belong to any of the layers whose bits are set in `mask`.
+    ///
+    /// # Remarks
+    /// Elements are assigned to layers through [`insert_with_mask()`]; all other
+    /// elements belong to all layers. Elements outside the requested layers are
+    /// skipped while scanning the leaves, before their bounding boxes are tested.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    /// * [`mask`] - The layer bits to query for.
+    pub fn intersect_aabb_masked(&self, rect: &AABB, mask: u32) -> Vec<ElementId> {
+        let mut matches = Vec::new();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    if elem_data.mask & mask != 0 && rect.intersects_with(&elem_data.rect) {
+                        matches.push(elem_id.clone());
+                    }
+                });
+            },
+        );
+        matches
+    }
+
+    /// Returns the ID of the element with the highest z-order containing the specified
+    /// point, e.g. to determine which of several overlapping UI elements was clicked.
+    ///
+    /// # Remarks
+    /// Points on the edges of an element are contained in it. If multiple elements
+    /// share the highest z-order, the one inserted last is returned.
+    /// Elements are assigned a z-order through [`insert_with_z()`].
+    ///
+    /// # Arguments
+    /// * [`point`] - The point to test for.
+    pub fn topmost_at_point(&self, point: Point) -> Option<ElementId> {
+        let mut topmost: Option<((i32, u64), ElementId)> = None;
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &AABB::from(point),
+            FindLeafHint::Query,
+            |_rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    if point.intersects_with(&elem_data.rect) {
+                        let order = (elem_data.z, elem_data.seq);
+                        let is_on_top = match topmost {
+                            Some((topmost_order, _)) => order > topmost_order,
+                            None => true,
+                        };
+                        if is_on_top {
+                            topmost = Some((order, elem_id.clone()));
+                        }
+                    }
+                });
+            },
+        );
+        topmost.map(|(_, id)| id)
+    }
+
+    /// Like [`intersect_aabb()`], but additionally reports statistics about the cost
+    /// of the query, e.g. in order to determine whether queries are slowed down by
+    /// too many leaves or by large elements spanning many quadrants.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb_instrumented(&self, rect: &AABB) -> (Vec<ElementId>, QueryStats) {
+        let mut stats = QueryStats::default();
+        let mut node_set = Vec::new();
+
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |rect, nd| {
+                stats.leaves_visited += 1;
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    stats.elements_tested += 1;
+                    if rect.intersects_with(&elem_data.rect) {
+                        stats.candidates_matched += 1;
+                        node_set.push(elem_id.clone());
+                    }
+                });
+            },
+        );
+
+        (node_set, stats)
+    }
+
+    /// Returns the set of IDs that occupy space within the specified bounding box,
+    /// together with the fraction of the tested elements that were rejected by the
+    /// final intersection test. Use it as a signal for tuning the tree's parameters;
+    /// see [`QueryStats::rejection_rate()`].
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb_with_rejection_rate(&self, rect: &AABB) -> (Vec<ElementId>, f32) {
+        let (node_set, stats) = self.intersect_aabb_instrumented(rect);
+        (node_set, stats.rejection_rate())
+    }
+
+    /// Returns the IDs of all elements that occupy space within the specified
+    /// bounding box, in the order in which the elements were inserted.
+    ///
+    /// # Remarks
+    /// Unlike [`intersect_aabb()`], whose order depends on the structure of the
+    /// tree, the order of the results only depends on the order of insertion.
+    /// Updating an element in place keeps its position, while removing and
+    /// inserting it again moves it to the end.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb_vec(&self, rect: &AABB) -> Vec<ElementId> {
+        let mut matches = Vec::new();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    if rect.intersects_with(&elem_data.rect) {
+                        matches.push((elem_data.seq, elem_id.clone()));
+                    }
+                });
+            },
+        );
+
+        matches.sort_unstable_by_key(|(seq, _)| *seq);
+        matches.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns a bitset of the element slots that occupy space within the specified
+    /// bounding box, e.g. to combine the results of multiple queries using set operations.
+    ///
+    /// # Remarks
+    /// Each bit refers to the internal slot of an element. Bitsets of queries against
+    /// the same, unmodified tree can be combined using bitwise operations, which is
+    /// much faster than intersecting sets of IDs for large results. Use
+    /// [`ids_from_bitset()`] to obtain the IDs of the elements afterwards.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb_bitset(&self, rect: &AABB) -> FixedBitSet {
+        let mut bits = FixedBitSet::with_capacity(self.element_ids.capacity());
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |element_idx, _, elem_data| {
+                    if rect.intersects_with(&elem_data.rect) {
+                        bits.insert(element_idx as usize);
+                    }
+                });
+            },
+        );
+        bits
+    }
+
+    /// Returns the IDs of the elements whose slots are set in the specified bitset,
+    /// ordered by slot.
+    ///
+    /// # Remarks
+    /// Bits of slots that are not occupied by an element are ignored. Since the slots
+    /// of removed elements are reused by later inserts, a bitset is only meaningful
+    /// for the tree it was obtained from, and only until the tree is modified.
+    /// This method visits all elements of the tree.
+    ///
+    /// # Arguments
+    /// * [`bits`] - The bitset obtained from [`intersect_aabb_bitset()`].
+    pub fn ids_from_bitset(&self, bits: &FixedBitSet) -> Vec<ElementId> {
+        let mut slots = Vec::with_capacity(bits.count_ones(..));
+        let aabb: AABB = self.root_rect.into();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            &aabb,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |element_idx, elem_id, _| {
+                    if bits.contains(element_idx as usize) {
+                        slots.push((element_idx, elem_id.clone()));
+                    }
+                });
+            },
+        );
+
+        slots.sort_unstable_by_key(|(element_idx, _)| *element_idx);
+        slots.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Returns the bounding boxes of the leaves in which a query for [`rect`] finds
+    /// the element with the specified ID, e.g. to debug why a query matched it.
+    ///
+    /// # Remarks
+    /// Elements spanning multiple quadrants of a branch are stored in the branch's
+    /// first child, which covers the same area as the branch itself; see [`QuadTreeCursor`].
+    /// The list is empty if the query does not match the element.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`rect`] - The rectangle to test for.
+    pub fn explain_match(&self, id: &ElementId, rect: &AABB) -> Vec<AABB> {
+        let mut cells = Vec::new();
+        self.find_leaves_aabb_fn(
+            self.get_root_node_data(),
+            rect,
+            FindLeafHint::Query,
+            |rect, nd| {
+                self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    if elem_id == id && rect.intersects_with(&elem_data.rect) {
+                        cells.push(nd.crect.get_aabb());
+                    }
+                });
+            },
+        );
+        cells
+    }
+
+    /// Returns an iterator over the IDs and bounding boxes of all elements that
+    /// occupy space within the specified bounding box.
+    ///
+    /// # Remarks
+    /// The elements are found lazily while iterating. Since every element is
+    /// stored in exactly one leaf, each element is yielded exactly once.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn iter_in_aabb(&self, rect: &AABB) -> AabbIterator<'_, ElementId> {
+        AabbIterator::new(self, *rect)
+    }
+
+    /// Returns a read-only cursor pointing at the root of the tree, e.g. for
+    /// custom traversals not covered by the built-in queries.
+    pub fn cursor(&self) -> QuadTreeCursor<'_, ElementId> {
+        QuadTreeCursor::new(self, self.get_root_node_data())
+    }
+
+    /// Builds a [`UniformGrid`] of the elements if all leaves of the tree are at the same
+    /// depth, e.g. after [`pre_split_to_depth()`], or returns `None` otherwise.
+    ///
+    /// # Remarks
+    /// For small depths, the grid answers queries faster than the tree since it
+    /// locates the cells covered by a query by index arithmetic. The grid is a
+    /// snapshot of the tree; it does not reflect later modifications.
+    pub fn as_uniform_grid(&self) -> Option<UniformGrid<ElementId>> {
+        let mut depth = None;
+        let mut is_uniform = true;
+        self.visit_leaves(|leaf| match depth {
+            None => depth = Some(leaf.depth()),
+            Some(d) => is_uniform &= d == leaf.depth(),
+        });
+        if !is_uniform {
+            return None;
+        }
+
+        Some(UniformGrid::new(
+            self.get_root_node_data().crect,
+            depth.unwrap_or(0),
+            self.all_elements(),
+        ))
+    }
+
+    /// Returns an iterator over the IDs and locations of all elements of a tree
+    /// storing points only, e.g. elements inserted through [`insert_point()`].
+    ///
+    /// # Remarks
+    /// The location of an element is the top-left corner of its bounding box,
+    /// which is the point itself for point elements.
+    ///
+    /// # Panics
+    /// In debug builds, panics if an element is not a point.
+    pub fn iter_points(&self) -> impl Iterator<Item = (ElementId, Point)> + '_ {
+        self.all_elements().map(|(id, rect)| {
+            debug_assert_eq!(rect.tl, rect.br, "the element is not a point");
+            (id, rect.tl)
+        })
+    }
+
+    /// Returns the smallest bounding box enclosing all elements, or `None` if
+    /// the tree is empty.
+    ///
+    /// # Remarks
+    /// Unlike the extents of the tree, which are fixed on construction, this
+    /// describes the area actually occupied by the elements.
+    pub fn elements_bounds(&self) -> Option<AABB> {
+        self.all_elements()
+            .map(|(_, rect)| rect)
+            .reduce(|bounds, rect| bounds + rect)
+    }
+
+    /// Returns an iterator over the IDs and bounding boxes of all elements.
+    fn all_elements(&self) -> AabbIterator<'_, ElementId> {
+        self.iter_in_aabb(&self.root_rect.into())
+    }
+
+    /// Returns a view of the tree whose queries are given in a coordinate system
+    /// offset from the one of the tree. Query rectangles are moved by the offset
+    /// before they are passed to the tree.
+    ///
+    /// # Arguments
+    /// * [`dx`] - The offset to add to the X coordinates of query rectangles.
+    /// * [`dy`] - The offset to add to the Y coordinates of query rectangles.
+    pub fn with_offset(&self, dx: i32, dy: i32) -> TranslatedView<'_, ElementId> {
+        TranslatedView::new(self, dx, dy)
+    }
+
+    /// Like [`intersect_aabb()`], but collects the IDs into a [`SmallVec`] that stores
+    /// up to eight IDs inline.
+    ///
+    /// # Remarks
+    /// The tree is traversed exactly like in [`intersect_aabb()`]; there is no separate
+    /// path for queries within a single leaf. Since such local queries typically match
+    /// at most `max_num_elements` elements, storing the result inline avoids its heap
+    /// allocation. Larger results spill to the heap as usual.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb_small(&self, rect: &AABB) -> SmallVec<[ElementId; 8]> {
+        let mut node_set = SmallVec::new();
+        self.intersect_aabb_fn(rect, |id| node_set.push(id));
+        node_set
+    }
+
+    /// Calls a function for each ID that occupies space within the
+    /// specified bounding box. The function may be called multiple
+    /// times for the same ID.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    /// * [`candidate_fn`] - The function called for each candidate element's ID.
+    #[inline]
+    pub fn intersect_aabb_fn<F>(&self, rect: &AABB, mut candidate_fn: F)
+    where
+        F: FnMut(ElementId),
+    {
+        let root = self.get_root_node_data();
+        self.find_leaves_aabb_fn(root, rect, FindLeafHint::Query, move |rect, nd| {
+            self.intersect_from_leaf(rect, nd, &mut |id| {
+                candidate_fn(id);
+            });
+        });
+    }
+
+    /// Calls a function for each ID that occupies space within the
+    /// specified bounding box, visiting the candidate leaves in order of
+    /// their distance to the reference point (near to far). The function
+    /// may be called multiple times for the same ID.
+    ///
+    /// # Remarks
+    /// Only the leaves are ordered; elements stored within the same leaf are
+    /// reported in storage order. Elements spanning multiple quadrants are stored
+    /// in a leaf covering the entire parent cell and are therefore reported
+    /// before the elements of the parent's quadrants.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    /// * [`from`] - The reference point used to order the leaves.
+    /// * [`candidate_fn`] - The function called for each candidate element's ID.
+    pub fn intersect_aabb_ordered<F>(&self, rect: &AABB, from: Point, mut candidate_fn: F)
+    where
+        F: FnMut(ElementId),
+    {
+        let root = self.get_root_node_data();
+        let mut leaves = Vec::new();
+        self.find_leaves_aabb_fn(root, rect, FindLeafHint::Query, |_rect, nd| {
+            leaves.push(nd);
+        });
+
+        leaves.sort_by_cached_key(|nd| nd.crect.get_aabb().squared_distance_to_point(&from));
+
+        for leaf in leaves {
+            self.intersect_from_leaf(rect, leaf, &mut candidate_fn);
+        }
+    }
+
+    /// Returns the union of the IDs that occupy space within any of the
+    /// specified bounding boxes. Each ID is reported at most once, even if
+    /// it is matched by multiple boxes.
+    ///
+    /// # Arguments
+    /// * [`rects`] - The rectangles to test for.
+    pub fn intersect_aabbs(&self, rects: &[AABB]) -> HashSet<ElementId> {
+        let mut node_set = HashSet::with_capacity(128);
+
+        // The traversal stack and the leaf list are shared by all queries.
+        let mut to_process = NodeList::default();
+        let mut leaves = NodeList::default();
+
+        for rect in rects {
+            self.find_leaves_aabb_with_fn(
+                self.get_root_node_data(),
+                rect,
+                FindLeafHint::Query,
+                &mut to_process,
+                |_rect, nd| leaves.push_back(nd),
+            );
+
+            while !leaves.is_empty() {
+                let leaf = leaves.pop_back();
+                self.intersect_from_leaf(rect, leaf, |id| {
+                    node_set.insert(id);
+                });
+            }
+        }
+
+        node_set
     }
 
-    /// Prunes unused child nodes from the tree.
+    /// Returns the `k` elements closest to the specified bounding box, together
+    /// with their squared distances, ordered from near to far. Elements intersecting
+    /// or touching the box have a distance of zero. Ties are reported in no particular order.
     ///
     /// # Remarks
-    /// The tree is never pruned automatically for performance reasons. Call
-    /// this method after all elements were removed or updated.
-    pub fn cleanup(&mut self) -> bool {
-        // Only process the root if it is not a leaf.
-        if self.nodes[0].is_leaf() {
-            return false;
+    /// The tree is traversed best-first: nodes are expanded in order of a lower
+    /// bound of the distance of the elements they can contain, such that the search
+    /// stops as soon as `k` elements were found that are not farther away than
+    /// any unexplored node.
+    ///
+    /// # Arguments
+    /// * [`query`] - The bounding box to measure the distances to.
+    /// * [`k`] - The maximum number of elements to return.
+    pub fn nearest_k_to_rect(&self, query: &AABB, k: usize) -> Vec<(ElementId, u64)> {
+        let mut nearest = Vec::with_capacity(k.min(self.element_ids.capacity()));
+        if k == 0 {
+            return nearest;
         }
 
-        let mut tree_compacted = false;
-
-        // Initialize the stack of nodes to be processed with the index of the root node.
-        // TODO: revisit the small list size, check element count
-        let mut to_process: SmallVec<[NodeIndexType; 128]> = smallvec::smallvec![0];
-
-        while !to_process.is_empty() {
-            let node_index = to_process.pop().unwrap();
-            let first_child_index = self.nodes[node_index as usize].get_first_child_node_index();
-
-            // Loop through the children.
-            let mut num_empty_leaves = 0usize;
-            for j in 0..5 {
-                let child_index = first_child_index + j;
-                let child = &self.nodes[child_index as usize];
+        // Elements only need their center to be within the root, so they may
+        // extend arbitrarily far beyond the root rectangle.
+        let unbounded = AABB::new(i32::MIN, i32::MIN, i32::MAX, i32::MAX);
 
-                // TODO: Compact nodes when the number of elements in child is less than allowed maximum.
+        let mut heap = BinaryHeap::new();
+        heap.push(DistanceEntry::new(
+            0,
+            NearestCandidate::Node(self.get_root_node_data(), unbounded),
+        ));
 
-                // Increment empty leaf count if the child is an empty
-                // leaf. Otherwise if the child is a branch, add it to
-                // the stack to be processed in the next iteration.
-                if child.is_empty() {
-                    num_empty_leaves += 1;
-                } else if child.is_branch() {
-                    to_process.push(child_index);
+        let mut children = NodeList::default();
+        while let Some(entry) = heap.pop() {
+            let (nd, bounds) = match entry.item {
+                NearestCandidate::Element(element_idx) => {
+                    let id = unsafe { self.element_ids.at(element_idx) }.clone();
+                    nearest.push((id, entry.distance));
+                    if nearest.len() == k {
+                        break;
+                    }
+                    continue;
                 }
-            }
-
-            // If all the children were empty leaves, remove them and
-            // make this node the new empty leaf.
-            if num_empty_leaves == 5 {
-                // Push all 5 children to the free list.
-                // (We don't change the indexes of the 2nd to 4th child because
-                // child nodes are always processed together.)
-                self.nodes[first_child_index as usize].first_child_or_element = self.free_node;
-                self.free_node = first_child_index;
+                NearestCandidate::Node(nd, bounds) => (nd, bounds),
+            };
 
-                // Make this node the new empty leaf.
-                self.nodes[node_index as usize].make_empty_leaf();
+            let node = &self.nodes[nd.index as usize];
+            if node.is_leaf() {
+                self.for_each_element_in_leaf(nd.index, |element_idx, _, elem_data| {
+                    heap.push(DistanceEntry::new(
+                        query.squared_distance_to(&elem_data.rect),
+                        NearestCandidate::Element(element_idx),
+                    ));
+                });
+                continue;
+            }
 
-                tree_compacted = true;
+            // Elements stored in a quadrant do not cross the parent's center lines,
+            // whereas elements of the "this" node are only bounded by the parent.
+            let (cx, cy) = (nd.crect.center_x, nd.crect.center_y);
+            let fc = node.get_first_child_node_index();
+            Self::collect_relevant_quadrants(
+                &mut children,
+                &nd,
+                fc,
+                Quadrants::all(),
+                FindLeafHint::Query,
+            );
+            while !children.is_empty() {
+                let child = children.pop_back();
+                let child_bounds = match child.index - fc {
+                    0 => bounds,
+                    1 => AABB::new(bounds.tl.x, bounds.tl.y, cx, cy),
+                    2 => AABB::new(cx, bounds.tl.y, bounds.br.x, cy),
+                    3 => AABB::new(bounds.tl.x, cy, cx, bounds.br.y),
+                    _ => AABB::new(cx, cy, bounds.br.x, bounds.br.y),
+                };
+                heap.push(DistanceEntry::new(
+                    query.squared_distance_to(&child_bounds),
+                    NearestCandidate::Node(child, child_bounds),
+                ));
             }
         }
 
-        tree_compacted
+        nearest
     }
 
-    /// Counts the total number of references. This number should be at least
-    /// the number of elements inserted; it will be higher if elements
-    /// span multiple cells.
-    #[allow(dead_code)]
-    pub(crate) fn count_element_references(&self) -> usize {
-        let mut to_process: SmallVec<[usize; 128]> = smallvec::smallvec![0];
-        let mut count = 0usize;
-        while !to_process.is_empty() {
-            let index = to_process.pop().unwrap();
-            let node = &self.nodes[index];
-            if node.is_branch() {
-                for j in 0..5 {
-                    to_process.push((node.first_child_or_element + j) as usize);
+    /// Returns the bounding box of the leaf closest to the specified point that
+    /// stores at least one element, or `None` if the tree is empty.
+    ///
+    /// # Remarks
+    /// This is a coarse approximation of the nearest element that only measures
+    /// the distance to the leaves rather than to the individual elements. Leaves
+    /// storing the elements spanning multiple quadrants report the bounding box
+    /// of their parent. Ties are resolved in no particular order.
+    ///
+    /// # Arguments
+    /// * [`point`] - The point to measure the distances to.
+    pub fn nearest_occupied_leaf(&self, point: Point) -> Option<AABB> {
+        let mut heap = BinaryHeap::new();
+        heap.push(DistanceEntry::new(0, self.get_root_node_data()));
+
+        let mut children = NodeList::default();
+        while let Some(entry) = heap.pop() {
+            let nd = entry.item;
+            let node = &self.nodes[nd.index as usize];
+            if node.is_leaf() {
+                if node.element_count > 0 {
+                    return Some(nd.crect.get_aabb());
                 }
-            } else {
-                count += node.element_count as usize;
+                continue;
             }
-        }
 
-        debug_assert!(count >= self.element_ids.debug_len());
-        debug_assert!(count >= self.element_rects.debug_len());
-        count
-    }
+            // Since every child is contained in its parent, the distances of the
+            // children are never smaller than the distance of the parent.
+            let fc = node.get_first_child_node_index();
+            Self::collect_relevant_quadrants(
+                &mut children,
+                &nd,
+                fc,
+                Quadrants::all(),
+                FindLeafHint::Query,
+            );
+            while !children.is_empty() {
+                let child = children.pop_back();
+                let distance = child.crect.get_aabb().squared_distance_to_point(&point);
+                heap.push(DistanceEntry::new(distance, child));
+            }
+        }
 
-    #[inline]
-    fn get_root_node_data(&self) -> NodeData {
-        NodeData::new_from_root(&self.root_rect, true)
+        None
     }
 
-    /// Returns the set of IDs that occupy space within the
-    /// specified bounding box.
+    /// Returns all pairs of elements of this tree and the `other` tree whose
+    /// bounding boxes intersect, e.g. for the broad phase of a collision test
+    /// between two sets of objects. Each pair holds the ID of the element of this
+    /// tree first. The pairs are returned in no particular order.
+    ///
+    /// # Remarks
+    /// The leaves of the `other` tree are visited one by one and this tree is queried
+    /// once per occupied leaf with the bounding box of the leaf's elements. Since every
+    /// element is stored in exactly one leaf, every pair is reported exactly once.
     ///
     /// # Arguments
-    /// * [`rect`] - The rectangle to test for.
-    #[inline]
-    pub fn intersect_aabb(&self, rect: &AABB) -> Vec<ElementId> {
-        let root = self.get_root_node_data();
-        let mut node_set = Vec::with_capacity(128);
+    /// * [`other`] - The tree to test the elements of this tree against.
+    pub fn intersect_tree(&self, other: &QuadTree<ElementId>) -> Vec<(ElementId, ElementId)> {
+        let mut pairs = Vec::new();
+        let mut others: Vec<(ElementId, AABB)> = Vec::new();
+        let mut candidates: Vec<(ElementId, AABB)> = Vec::new();
+
+        let other_rect: AABB = other.root_rect.into();
+        other.find_leaves_aabb_fn(
+            other.get_root_node_data(),
+            &other_rect,
+            FindLeafHint::Query,
+            |_rect, nd| {
+                let node = &other.nodes[nd.index as usize];
+                if node.is_empty() {
+                    return;
+                }
 
-        self.find_leaves_aabb_fn(root, rect, FindLeafHint::Query, |rect, nd| {
-            self.intersect_from_leaf(rect, nd, &mut |id| {
-                node_set.push(id);
-            });
-        });
+                // Gather the leaf's elements and the box enclosing all of them.
+                others.clear();
+                other.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                    others.push((elem_id.clone(), elem_data.rect));
+                });
+                let bounds = others
+                    .iter()
+                    .skip(1)
+                    .fold(others[0].1, |bounds, (_, rect)| bounds + *rect);
+
+                candidates.clear();
+                self.find_leaves_aabb_fn(
+                    self.get_root_node_data(),
+                    &bounds,
+                    FindLeafHint::Query,
+                    |bounds, nd| {
+                        self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                            if bounds.intersects_with(&elem_data.rect) {
+                                candidates.push((elem_id.clone(), elem_data.rect));
+                            }
+                        });
+                    },
+                );
+
+                for (id, rect) in candidates.iter() {
+                    for (other_id, other_rect) in others.iter() {
+                        if rect.intersects_with(other_rect) {
+                            pairs.push((id.clone(), other_id.clone()));
+                        }
+                    }
+                }
+            },
+        );
 
-        node_set
+        pairs
     }
 
-    /// Calls a function for each ID that occupies space within the
-    /// specified bounding box. The function may be called multiple
-    /// times for the same ID.
+    /// Returns the IDs of all elements hit by the specified ray together with
+    /// the `t` at which the ray enters them, ordered from near to far.
+    ///
+    /// # Remarks
+    /// Elements behind the origin of the ray are not reported, while elements
+    /// containing the origin are reported with a `t` of zero. Since every element
+    /// is stored in exactly one leaf, each element is reported exactly once.
     ///
     /// # Arguments
-    /// * [`rect`] - The rectangle to test for.
-    /// * [`candidate_fn`] - The function called for each candidate element's ID.
-    #[inline]
-    pub fn intersect_aabb_fn<F>(&self, rect: &AABB, mut candidate_fn: F)
-    where
-        F: FnMut(ElementId),
-    {
-        let root = self.get_root_node_data();
-        self.find_leaves_aabb_fn(root, rect, FindLeafHint::Query, move |rect, nd| {
-            self.intersect_from_leaf(rect, nd, &mut |id| {
-                candidate_fn(id);
+    /// * [`ray`] - The ray to cast.
+    pub fn raycast_all(&self, ray: &Ray) -> Vec<(ElementId, f32)> {
+        let mut hits = Vec::new();
+        self.find_leaves_generic_fn(self.get_root_node_data(), ray, |nd| {
+            self.for_each_element_in_leaf(nd.index, |_, elem_id, elem_data| {
+                if let Some(t) = ray.entry_t(&elem_data.rect) {
+                    hits.push((elem_id.clone(), t));
+                }
             });
         });
+
+        hits.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        hits
     }
 
     /// Returns the set of IDs that occupy space within the
     /// specified bounding box.
     ///
+    /// # Remarks
+    /// The element can be passed by value or by reference.
+    ///
     /// # Arguments
     /// * [`element`] - The element to test for.
+    ///
+    /// # Example
+    /// ```rust
+    /// use space_partitioning::intersections::Ray;
+    /// use space_partitioning::quadtree::{Point, QuadRect, QuadTreeElement, AABB};
+    /// use space_partitioning::QuadTree;
+    ///
+    /// let mut tree = QuadTree::new(QuadRect::new(0, 0, 64, 64), 4, 4, 1);
+    /// tree.insert(QuadTreeElement::new(1, AABB::new(8, 8, 16, 16))).unwrap();
+    ///
+    /// assert_eq!(tree.intersect_generic(Point::new(10, 10)), [1]);
+    ///
+    /// let ray = Ray::new(0.0, 12.0, 1.0, 0.0);
+    /// assert_eq!(tree.intersect_generic(&ray), [1]);
+    /// ```
     #[inline]
-    pub fn intersect_generic<T>(&self, element: &T) -> Vec<ElementId>
+    pub fn intersect_generic<T>(&self, element: T) -> Vec<ElementId>
     where
         T: IntersectsWith<AABB>,
     {
+        let element = &element;
         let root = self.get_root_node_data();
         let mut node_set = Vec::with_capacity(128);
 
@@ -712,14 +2647,15 @@ where
     /// times for the same ID.
     ///
     /// # Arguments
-    /// * [`element`] - The element to test for.
+    /// * [`element`] - The element to test for, passed by value or by reference.
     /// * [`candidate_fn`] - The function called for each candidate element's ID.
     #[inline]
-    pub fn intersect_generic_fn<T, F>(&self, element: &T, mut candidate_fn: F)
+    pub fn intersect_generic_fn<T, F>(&self, element: T, mut candidate_fn: F)
     where
         T: IntersectsWith<AABB>,
         F: FnMut(ElementId),
     {
+        let element = &element;
         let root = self.get_root_node_data();
         self.find_leaves_generic_fn(root, element, move |nd| {
             self.intersect_from_leaf(element, nd, &mut |id| {
@@ -734,21 +2670,32 @@ where
         T: IntersectsWith<AABB>,
         F: FnMut(ElementId),
     {
-        let leaf = self.nodes[leaf_data.index as usize];
+        self.for_each_element_in_leaf(leaf_data.index, |_, elem_id, elem_data| {
+            // Depending on the size of the quadrant, the candidate element
+            // might still not be covered by the search rectangle.
+            if element.intersects_with(&elem_data.rect) {
+                candidate_fn(elem_id.clone());
+            }
+        });
+    }
+
+    /// Calls a function for each element stored in the specified leaf, passing
+    /// the index, the ID and the data of the element.
+    #[inline]
+    fn for_each_element_in_leaf<F>(&self, leaf_index: NodeIndexType, mut element_fn: F)
+    where
+        F: FnMut(free_list::IndexType, &ElementId, &ElementData),
+    {
+        let leaf = &self.nodes[leaf_index as usize];
         debug_assert!(leaf.is_leaf());
 
         let mut elem_node_idx = leaf.first_child_or_element;
         while elem_node_idx != free_list::SENTINEL {
             let elem_node = unsafe { self.element_nodes.at(elem_node_idx) };
-            let elem_rect = unsafe { self.element_rects.at(elem_node.element_idx) };
-
-            // Depending on the size of the quadrant, the candidate element
-            // might still not be covered by the search rectangle.
-            if element.intersects_with(&elem_rect) {
-                let elem_id = *unsafe { self.element_ids.at(elem_node.element_idx) };
-                candidate_fn(elem_id);
-            }
-
+            let element_idx = elem_node.element_idx;
+            let elem_id = unsafe { self.element_ids.at(element_idx) };
+            let elem_data = unsafe { self.element_data.at(element_idx) };
+            element_fn(element_idx, elem_id, elem_data);
             elem_node_idx = elem_node.next;
         }
     }
@@ -806,6 +2753,509 @@ pub(crate) fn build_test_tree() -> QuadTree {
 mod test {
     use super::*;
 
+    #[test]
+    fn validate_detects_corruption() {
+        let mut tree = build_test_tree();
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Claim that a non-empty leaf holds one more element than it does.
+        let leaf = tree
+            .nodes
+            .iter()
+            .position(|node| node.is_leaf() && !node.is_empty())
+            .expect("tree has non-empty leaves");
+        tree.nodes[leaf].element_count += 1;
+        let error = tree.validate().expect_err("corruption should be detected");
+        assert!(error.starts_with(&format!("leaf {}: element count", leaf)));
+        tree.nodes[leaf].element_count -= 1;
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Let the root's children overlap with another block.
+        let first_child = tree.nodes[0].first_child_or_element;
+        tree.nodes[0].first_child_or_element = first_child + 1;
+        assert!(tree.validate().is_err());
+        tree.nodes[0].first_child_or_element = first_child;
+
+        // Put the root's children into the free list while they are still in use.
+        tree.free_node = first_child;
+        let error = tree.validate().expect_err("corruption should be detected");
+        assert!(error.contains("referenced twice"));
+    }
+
+    #[test]
+    fn find_duplicate_ids_works() {
+        let mut tree = build_test_tree();
+        assert!(tree.find_duplicate_ids().is_empty());
+
+        // Inserts do not check for duplicate IDs under the default policy.
+        for (id, rect) in [
+            (2000, AABB::new(-15, -15, -5, -5)),
+            (2000, AABB::new(5, 5, 15, 15)),
+            (4000, AABB::new(-2, -2, 2, 2)),
+        ] {
+            tree.insert(QuadTreeElement::new(id, rect))
+                .expect("insert should work");
+        }
+
+        let mut duplicates = tree.find_duplicate_ids();
+        duplicates.sort_unstable();
+        assert_eq!(duplicates, vec![2000, 4000]);
+    }
+
+    #[test]
+    fn duplicate_policy_allow_keeps_both_elements() {
+        let mut tree = build_test_tree();
+        assert_eq!(tree.duplicate_policy(), DuplicatePolicy::Allow);
+        tree.insert(QuadTreeElement::new(2000, AABB::new(-19, -3, -16, 0)))
+            .expect("insert should work");
+
+        assert_eq!(tree.len(), 7);
+        assert_eq!(tree.find_duplicate_ids(), vec![2000]);
+        assert!(tree
+            .intersect_aabb(&AABB::new(-18, -2, -17, -1))
+            .contains(&2000));
+        assert!(tree
+            .intersect_aabb(&AABB::new(8, -12, 12, -8))
+            .contains(&2000));
+    }
+
+    #[test]
+    fn duplicate_policy_allow_removes_one_of_two_elements() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 1, 4, 1);
+        let first = QuadTreeElement::new(7, AABB::new(-15, -15, -10, -10));
+        let second = QuadTreeElement::new(7, AABB::new(-8, -8, -6, -6));
+        tree.insert(first).expect("insert should work");
+        tree.insert(second).expect("insert should work");
+        assert_eq!(tree.references(), 2);
+
+        // Both elements are stored in the same leaf; only the second one is removed.
+        assert!(tree.remove(&second));
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree.intersect_aabb(&AABB::new(-14, -14, -12, -12)), [7]);
+        assert!(tree.intersect_aabb(&AABB::new(-7, -7, -7, -7)).is_empty());
+        assert!(!tree.remove(&second));
+
+        assert!(tree.remove(&first));
+        assert!(tree.is_empty());
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn duplicate_policy_reject_works() {
+        let mut tree = build_test_tree();
+        tree.set_duplicate_policy(DuplicatePolicy::Reject);
+        let generation = tree.generation();
+
+        assert!(matches!(
+            tree.insert(QuadTreeElement::new(2000, AABB::new(-19, -3, -16, 0))),
+            Err(InsertError::DuplicateId)
+        ));
+
+        assert_eq!(tree.generation(), generation);
+        assert_eq!(tree.len(), 6);
+        assert!(tree.intersect_aabb(&AABB::new(-18, -2, -17, -1)).is_empty());
+        assert!(tree
+            .intersect_aabb(&AABB::new(8, -12, 12, -8))
+            .contains(&2000));
+
+        tree.insert(QuadTreeElement::new(2001, AABB::new(-19, -3, -16, 0)))
+            .expect("insert should work");
+        assert_eq!(tree.len(), 7);
+    }
+
+    #[test]
+    fn duplicate_policy_replace_works() {
+        let mut tree = build_test_tree();
+        tree.set_duplicate_policy(DuplicatePolicy::Replace);
+        tree.set_max_elements(Some(tree.len()));
+
+        // Replacing an element does not count against the maximum number of elements.
+        tree.insert_with_z(QuadTreeElement::new(2000, AABB::new(-19, -3, -16, 0)), 3)
+            .expect("replace should work");
+
+        assert_eq!(tree.len(), 6);
+        assert_eq!(tree.references(), 6);
+        assert!(tree.find_duplicate_ids().is_empty());
+        assert!(tree.intersect_aabb(&AABB::new(8, -12, 12, -8)).is_empty());
+        assert_eq!(tree.topmost_at_point(Point::new(-18, -2)), Some(2000));
+        assert!(tree.remove(&QuadTreeElement::new(2000, AABB::new(-19, -3, -16, 0))));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn with_capacity_does_not_reallocate() {
+        const N: u32 = 1000;
+        let mut tree = QuadTree::with_capacity(QuadRect::new(0, 0, 256, 256), 6, 4, 1, 1024, 1024);
+        let reserved_ids = tree.element_ids.reserved_capacity();
+        let reserved_nodes = tree.element_nodes.reserved_capacity();
+        assert!(reserved_nodes >= 1024);
+
+        for id in 0..N {
+            let x = (id as i32 * 37) % 250;
+            let y = (id as i32 * 91) % 250;
+            tree.insert(QuadTreeElement::new(id, AABB::new(x, y, x + 4, y + 4)))
+                .expect("insert should work");
+        }
+
+        assert_eq!(tree.references(), N as usize);
+        assert_eq!(tree.element_ids.reserved_capacity(), reserved_ids);
+        assert_eq!(tree.element_nodes.reserved_capacity(), reserved_nodes);
+    }
+
+    #[test]
+    fn sort_leaf_elements_works() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 6, 16, 1);
+        let element = |id: u32| {
+            let x = (id as i32 * 37) % 250;
+            let y = (id as i32 * 91) % 250;
+            QuadTreeElement::new(id, AABB::new(x, y, x + 4, y + 4))
+        };
+
+        // Churn the tree such that the freed element slots are reused out of order.
+        for id in 0..400 {
+            tree.insert(element(id)).expect("insert should work");
+        }
+        for id in (0..400).filter(|id| id % 3 == 0) {
+            assert!(tree.remove(&element(id)));
+        }
+        for id in 400..500 {
+            tree.insert(element(id)).expect("insert should work");
+        }
+
+        let query = AABB::new(0, 0, 256, 256);
+        let expected = tree.intersect_aabb_vec(&query);
+        let generation = tree.generation();
+
+        tree.sort_leaf_elements();
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.generation(), generation);
+        assert_eq!(tree.intersect_aabb_vec(&query), expected);
+
+        let mut leaves = Vec::new();
+        tree.find_leaves_aabb_fn(
+            tree.get_root_node_data(),
+            &query,
+            FindLeafHint::Query,
+            |_rect, nd| leaves.push(nd.index),
+        );
+        for leaf_index in leaves {
+            let mut element_indices = Vec::new();
+            let mut elem_node_idx = tree.nodes[leaf_index as usize].first_child_or_element;
+            while elem_node_idx != free_list::SENTINEL {
+                let elem_node = unsafe { tree.element_nodes.at(elem_node_idx) };
+                element_indices.push(elem_node.element_idx);
+                elem_node_idx = elem_node.next;
+            }
+            assert!(element_indices.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn rebuild_compacts_the_tree() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 6, 4, 1);
+        let element = |id: u32| {
+            let x = (id as i32 * 37) % 250;
+            let y = (id as i32 * 91) % 250;
+            QuadTreeElement::new(id, AABB::new(x, y, x + 4, y + 4))
+        };
+
+        // Churn the tree such that only every fourth element remains.
+        for id in 0..400 {
+            tree.insert(element(id)).expect("insert should work");
+        }
+        for id in (0..400).filter(|id| id % 4 != 0) {
+            assert!(tree.remove(&element(id)));
+        }
+        tree.cleanup();
+
+        let queries = [
+            AABB::new(0, 0, 256, 256),
+            AABB::new(10, 10, 60, 60),
+            AABB::new(100, 0, 140, 256),
+        ];
+        let expected: Vec<_> = queries.iter().map(|q| tree.intersect_aabb_vec(q)).collect();
+        let nodes_before = tree.nodes.len();
+        assert_eq!(tree.element_ids.capacity(), 400);
+
+        tree.rebuild();
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.len(), 100);
+        assert_eq!(tree.element_ids.capacity(), 100);
+        assert_eq!(tree.element_nodes.capacity(), 100);
+        assert!(tree.nodes.len() <= nodes_before);
+
+        let actual: Vec<_> = queries.iter().map(|q| tree.intersect_aabb_vec(q)).collect();
+        assert_eq!(actual, expected);
+        for id in (0..400).step_by(4) {
+            assert!(tree.remove(&element(id)));
+        }
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn shrink_to_fit_releases_memory() {
+        let mut tree = QuadTree::new(QuadRect::new(0, 0, 256, 256), 6, 4, 1);
+        let element = |id: u32| {
+            let x = (id as i32 % 20) * 12;
+            let y = (id as i32 / 20) * 12;
+            QuadTreeElement::new(id, AABB::new(x, y, x + 4, y + 4))
+        };
+
+        // Simulate a load spike after which only the first row remains.
+        for id in 0..400 {
+            tree.insert(element(id)).expect("insert should work");
+        }
+        for id in 20..400 {
+            assert!(tree.remove(&element(id)));
+        }
+        assert!(tree.cleanup());
+
+        let nodes_before = tree.nodes.capacity();
+        let query = AABB::new(0, 0, 256, 256);
+        let expected = tree.intersect_aabb_vec(&query);
+        assert_eq!(tree.element_ids.capacity(), 400);
+
+        tree.shrink_to_fit();
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.nodes.capacity() < nodes_before);
+        assert_eq!(tree.element_ids.capacity(), 20);
+        assert_eq!(tree.element_data.reserved_capacity(), 20);
+        assert_eq!(tree.intersect_aabb_vec(&query), expected);
+
+        // The tree keeps working after shrinking.
+        for id in 20..400 {
+            tree.insert(element(id)).expect("insert should work");
+        }
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.intersect_aabb(&query).len(), 400);
+    }
+
+    #[test]
+    fn pre_split_to_depth_works() {
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 1, 1);
+        tree.pre_split_to_depth(2);
+
+        // The root and five nodes per split: one split at depth 0, four at depth 1.
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4));
+        assert_eq!(tree.validate(), Ok(()));
+
+        // Splitting again only subdivides the leaves of the deepest level.
+        tree.pre_split_to_depth(3);
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4 + 16));
+
+        // One element per leaf is placed without splitting.
+        for (id, (x, y)) in (0..8)
+            .flat_map(|x| (0..8).map(move |y| (x * 8 + 2, y * 8 + 2)))
+            .enumerate()
+        {
+            let outcome = tree
+                .insert_reporting(QuadTreeElement::new(
+                    id as u32,
+                    AABB::new(x, y, x + 1, y + 1),
+                ))
+                .expect("insert should work");
+            assert_eq!(outcome.splits_performed, 0);
+            assert_eq!(outcome.final_depth, 3);
+        }
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn visit_leaves_morton_works() {
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 1, 1);
+        tree.pre_split_to_depth(2);
+
+        let mut corners = Vec::new();
+        tree.visit_leaves_morton(|leaf| {
+            let rect = leaf.get_aabb();
+            corners.push((rect.tl.x, rect.tl.y));
+        });
+
+        assert_eq!(
+            corners,
+            [
+                (0, 0),
+                (16, 0),
+                (0, 16),
+                (16, 16),
+                (32, 0),
+                (48, 0),
+                (32, 16),
+                (48, 16),
+                (0, 32),
+                (16, 32),
+                (0, 48),
+                (16, 48),
+                (32, 32),
+                (48, 32),
+                (32, 48),
+                (48, 48)
+            ]
+        );
+    }
+
+    #[test]
+    fn set_max_num_elements_works() {
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 64, 1);
+        for (id, (x, y)) in (0..8)
+            .flat_map(|x| (0..8).map(move |y| (x * 8 + 2, y * 8 + 2)))
+            .enumerate()
+        {
+            tree.insert(QuadTreeElement::new(
+                id as u32,
+                AABB::new(x, y, x + 1, y + 1),
+            ))
+            .expect("insert should work");
+        }
+        assert_eq!(tree.nodes.len(), 1);
+
+        assert_eq!(tree.set_max_num_elements(0), Err(()));
+        assert_eq!(tree.nodes.len(), 1);
+
+        // Lowering the threshold splits the root down to one element per leaf.
+        let generation = tree.generation();
+        assert_eq!(tree.set_max_num_elements(1), Ok(()));
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.generation() > generation);
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4 + 16));
+        tree.visit_leaves(|leaf| assert!(leaf.element_count <= 1));
+
+        let query = AABB::new(0, 0, 64, 64);
+        assert_eq!(tree.intersect_aabb(&query).len(), 64);
+        assert_eq!(tree.count_element_references(), 64);
+
+        // Raising the threshold collapses the branches again.
+        assert_eq!(tree.set_max_num_elements(64), Ok(()));
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.nodes[0].is_leaf());
+        assert_eq!(tree.intersect_aabb(&query).len(), 64);
+    }
+
+    #[test]
+    fn visit_leaf_neighbors_works() {
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 1, 1);
+        tree.pre_split_to_depth(2);
+
+        let mut neighbor_counts = Vec::new();
+        tree.visit_leaf_neighbors(|leaf, neighbors| {
+            let aabb = leaf.get_aabb();
+            for neighbor in neighbors {
+                assert!(!neighbor.get_aabb().intersects_with(&aabb));
+            }
+            if aabb.tl.x == 0 && aabb.tl.y == 0 {
+                // The top-left corner cell of the 4x4 grid.
+                assert_eq!(neighbors.len(), 2);
+            }
+            neighbor_counts.push(neighbors.len());
+        });
+
+        // Four corner cells, eight edge cells and four inner cells.
+        neighbor_counts.sort_unstable();
+        assert_eq!(
+            neighbor_counts,
+            [[2; 4].as_slice(), &[3; 8], &[4; 4]].concat()
+        );
+    }
+
+    #[test]
+    fn visit_leaf_neighbors_of_unsplit_tree_is_empty() {
+        let tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 4, 1, 1);
+        let mut visited = 0;
+        tree.visit_leaf_neighbors(|_, neighbors| {
+            assert!(neighbors.is_empty());
+            visited += 1;
+        });
+        assert_eq!(visited, 1);
+    }
+
+    #[test]
+    fn pre_split_to_depth_respects_limits() {
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 2, 1, 1);
+        tree.pre_split_to_depth(8);
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4));
+
+        let mut tree = QuadTree::<u32>::new(QuadRect::new(0, 0, 64, 64), 8, 1, 16);
+        tree.pre_split_to_depth(8);
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4));
+    }
+
+    #[test]
+    fn pre_split_to_depth_distributes_elements() {
+        let mut tree = QuadTree::new(QuadRect::new(-20, -20, 40, 40), 4, 8, 1);
+        tree.insert(QuadTreeElement::new(1000, AABB::new(-15, -15, -5, -5)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(4000, AABB::new(5, 5, 15, 15)))
+            .expect("insert should work");
+        tree.insert(QuadTreeElement::new(5000, AABB::new(-5, -5, 5, 5)))
+            .expect("insert should work");
+        assert_eq!(tree.nodes.len(), 1);
+
+        tree.pre_split_to_depth(2);
+        assert_eq!(tree.nodes.len(), 1 + 5 * (1 + 4));
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.count_element_references(), 3);
+
+        let mut results = tree.intersect_aabb(&AABB::new(-17, -17, 0, 0));
+        results.sort_unstable();
+        assert_eq!(results, vec![1000, 5000]);
+    }
+
+    #[test]
+    fn cleanup_merges_sparse_leaves() {
+        let mut tree = QuadTree::new(QuadRect::new(-32, -32, 64, 64), 3, 2, 1);
+        let elements = [
+            // Splits the top-left quadrant.
+            QuadTreeElement::new(0, AABB::new(-30, -30, -28, -28)),
+            QuadTreeElement::new(1, AABB::new(-6, -30, -4, -28)),
+            QuadTreeElement::new(2, AABB::new(-30, -6, -28, -4)),
+            // Splits the bottom-right quadrant.
+            QuadTreeElement::new(3, AABB::new(20, 20, 22, 22)),
+            QuadTreeElement::new(4, AABB::new(24, 24, 26, 26)),
+            QuadTreeElement::new(5, AABB::new(6, 6, 8, 8)),
+        ];
+        for element in elements {
+            tree.insert(element).expect("insert should work");
+        }
+
+        let top_left = tree.nodes[0].get_first_child_node_index() as usize + 1;
+        let bottom_right = top_left + 3;
+        assert!(tree.nodes[top_left].is_branch());
+        assert!(tree.nodes[bottom_right].is_branch());
+
+        // Leave two sparse leaves in the top-left quadrant.
+        assert!(tree.remove(&elements[2]));
+        assert!(tree.cleanup());
+        assert!(tree.nodes[top_left].is_leaf());
+        assert_eq!(tree.nodes[top_left].element_count, 2);
+        assert!(tree.nodes[bottom_right].is_branch());
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.collect_ids().len(), 5);
+
+        // Nothing left to merge, since the root's children hold three elements in total.
+        assert!(tree.remove(&elements[3]));
+        assert!(tree.remove(&elements[4]));
+        assert!(tree.cleanup());
+        assert!(tree.nodes[0].is_branch());
+        assert!(tree.nodes[bottom_right].is_leaf());
+        assert!(!tree.cleanup());
+
+        // Merging cascades up to the root.
+        assert!(tree.remove(&elements[5]));
+        tree.insert(elements[2]).expect("insert should work");
+        assert!(tree.remove(&elements[2]));
+        assert!(tree.cleanup());
+        assert!(tree.nodes[0].is_leaf());
+        assert_eq!(tree.nodes[0].element_count, 2);
+        assert_eq!(tree.validate(), Ok(()));
+
+        let mut ids = tree.collect_ids();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![0, 1]);
+        assert!(tree.remove(&elements[0]));
+        assert!(tree.remove(&elements[1]));
+        assert_eq!(tree.count_element_references(), 0);
+    }
+
     #[test]
     fn cleanup_works() {
         let quad_rect = QuadRect::new(-20, -20, 40, 40);
@@ -1,10 +1,10 @@
 use crate::quadtree::{free_list, AABB};
 
 /// Alias for all traits required for an element ID.
-pub trait ElementIdType: Default + std::cmp::Eq + std::hash::Hash + Copy {}
+pub trait ElementIdType: Default + std::cmp::Eq + std::hash::Hash + Clone {}
 
 /// Helper implementation to automatically derive the [`ElementIdType`] trait
-impl<T> ElementIdType for T where T: Default + std::cmp::Eq + std::hash::Hash + Copy {}
+impl<T> ElementIdType for T where T: Default + std::cmp::Eq + std::hash::Hash + Clone {}
 
 /// Represents an element in the QuadTree.
 #[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
@@ -41,3 +41,16 @@ pub(crate) struct QuadTreeElementNode {
     /// Stores the element index.
     pub element_idx: free_list::IndexType,
 }
+
+/// Stores the data of an element in the quadtree, besides its ID.
+#[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
+pub(crate) struct ElementData {
+    /// The axis-aligned bounding box of the element.
+    pub rect: AABB,
+    /// The insertion sequence number, used to report query results in a stable order.
+    pub seq: u64,
+    /// The layer mask, used to filter query results by layer.
+    pub mask: u32,
+    /// The z-order, used to find the topmost of overlapping elements.
+    pub z: i32,
+}
@@ -0,0 +1,30 @@
+/// Describes the cost of a query, as reported by [`QuadTree::intersect_aabb_instrumented()`].
+///
+/// [`QuadTree::intersect_aabb_instrumented()`]: crate::quadtree::QuadTree::intersect_aabb_instrumented
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct QueryStats {
+    /// The number of leaves whose elements were tested, including "this" nodes
+    /// holding the elements that span the quadrants of their parent.
+    pub leaves_visited: u32,
+    /// The number of elements whose bounding boxes were tested against the query.
+    pub elements_tested: u32,
+    /// The number of elements that matched the query.
+    pub candidates_matched: u32,
+}
+
+impl QueryStats {
+    /// Returns the fraction of the tested elements that did not match the query,
+    /// or zero if no element was tested.
+    ///
+    /// # Remarks
+    /// A high rejection rate indicates that the cells of the tree are too coarse
+    /// relative to the sizes of the elements and queries, e.g. because the maximum
+    /// depth or the smallest cell size prevent further splits.
+    pub fn rejection_rate(&self) -> f32 {
+        if self.elements_tested == 0 {
+            return 0.0;
+        }
+        let rejected = self.elements_tested - self.candidates_matched;
+        rejected as f32 / self.elements_tested as f32
+    }
+}
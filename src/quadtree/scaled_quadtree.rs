@@ -0,0 +1,175 @@
+use crate::quadtree::error::InsertError;
+use crate::quadtree::quadtree::{ElementIdType, QuadTree, QuadTreeElement};
+use crate::quadtree::AABB;
+
+/// A wrapper around a [`QuadTree`] that accepts floating-point coordinates.
+///
+/// # Remarks
+/// Coordinates are multiplied by the scale factor and rounded outwards, i.e.
+/// the minimum corner is floored and the maximum corner is ceiled. The integral
+/// boxes therefore never shrink below the scaled floating-point boxes, which
+/// means queries may report additional candidates but never miss an element.
+/// Values exceeding the `i32` range are clamped.
+pub struct ScaledQuadTree<ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    /// The wrapped tree operating on scaled integral coordinates.
+    tree: QuadTree<ElementId>,
+    /// The factor applied to all coordinates before rounding.
+    scale: f32,
+}
+
+impl<ElementId> ScaledQuadTree<ElementId>
+where
+    ElementId: ElementIdType,
+{
+    /// Wraps a tree whose bounds are given in scaled (integral) coordinates.
+    ///
+    /// # Arguments
+    /// * [`tree`] - The tree to wrap.
+    /// * [`scale`] - The factor applied to all coordinates; must be positive.
+    pub fn new(tree: QuadTree<ElementId>, scale: f32) -> Self {
+        assert!(scale.is_finite() && scale > 0.0);
+        Self { tree, scale }
+    }
+
+    /// Gets the factor applied to all coordinates.
+    #[inline]
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Gets the wrapped tree.
+    #[inline]
+    pub fn inner(&self) -> &QuadTree<ElementId> {
+        &self.tree
+    }
+
+    /// Unwraps the tree.
+    #[inline]
+    pub fn into_inner(self) -> QuadTree<ElementId> {
+        self.tree
+    }
+
+    /// Converts a rectangle `[x1, y1, x2, y2]` to the scaled integral box covering it.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle in unscaled floating-point coordinates.
+    #[inline]
+    pub fn to_aabb(&self, rect: [f32; 4]) -> AABB {
+        AABB::from_f32_conservative(
+            [rect[0] * self.scale, rect[1] * self.scale],
+            [rect[2] * self.scale, rect[3] * self.scale],
+        )
+    }
+
+    /// Inserts an element given by its rectangle `[x1, y1, x2, y2]`.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`rect`] - The rectangle in unscaled floating-point coordinates.
+    pub fn insert_f32(&mut self, id: ElementId, rect: [f32; 4]) -> Result<(), InsertError> {
+        let rect = self.to_aabb(rect);
+        self.tree.insert(QuadTreeElement::new(id, rect))
+    }
+
+    /// Removes an element given by its rectangle `[x1, y1, x2, y2]`.
+    ///
+    /// # Remarks
+    /// The rectangle must be the same that was used to insert the element.
+    ///
+    /// # Arguments
+    /// * [`id`] - The ID of the element.
+    /// * [`rect`] - The rectangle in unscaled floating-point coordinates.
+    pub fn remove_f32(&mut self, id: ElementId, rect: [f32; 4]) -> bool {
+        let rect = self.to_aabb(rect);
+        self.tree.remove(&QuadTreeElement::new(id, rect))
+    }
+
+    /// Returns the set of IDs that occupy space within the specified rectangle `[x1, y1, x2, y2]`.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle in unscaled floating-point coordinates.
+    pub fn intersect_aabb_f32(&self, rect: [f32; 4]) -> Vec<ElementId> {
+        self.tree.intersect_aabb(&self.to_aabb(rect))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quadtree::QuadRect;
+
+    fn build_scaled_tree() -> ScaledQuadTree {
+        // The tree covers [-20, 20] in unscaled coordinates.
+        let tree = QuadTree::new(QuadRect::new(-2000, -2000, 4000, 4000), 8, 1, 1);
+        ScaledQuadTree::new(tree, 100.0)
+    }
+
+    #[test]
+    fn to_aabb_is_conservative() {
+        let tree = build_scaled_tree();
+        let rects = [
+            [0.001, 0.001, 0.002, 0.002],
+            [-1.234, -5.678, 1.234, 5.678],
+            [-10.005, 3.3333, -9.9999, 3.3334],
+            [7.0, 7.0, 7.0, 7.0],
+        ];
+
+        for rect in rects {
+            let aabb = tree.to_aabb(rect);
+            assert!(aabb.tl.x as f32 <= rect[0] * tree.scale());
+            assert!(aabb.tl.y as f32 <= rect[1] * tree.scale());
+            assert!(aabb.br.x as f32 >= rect[2] * tree.scale());
+            assert!(aabb.br.y as f32 >= rect[3] * tree.scale());
+        }
+    }
+
+    #[test]
+    fn to_aabb_clamps() {
+        let tree = build_scaled_tree();
+        let aabb = tree.to_aabb([-1e30, -1e30, 1e30, 1e30]);
+        assert_eq!(aabb, AABB::new(i32::MIN, i32::MIN, i32::MAX, i32::MAX));
+    }
+
+    #[test]
+    fn insert_and_query_works() {
+        let mut tree = build_scaled_tree();
+        tree.insert_f32(1, [-10.5, -10.5, -10.25, -10.25])
+            .expect("insert should work");
+        tree.insert_f32(2, [0.001, 0.001, 0.002, 0.002])
+            .expect("insert should work");
+        tree.insert_f32(3, [10.0, 10.0, 12.5, 12.5])
+            .expect("insert should work");
+
+        // Queries touching tiny fractions of the elements still find them.
+        let results = tree.intersect_aabb_f32([-10.26, -10.26, -10.255, -10.255]);
+        assert_eq!(results, [1]);
+
+        let results = tree.intersect_aabb_f32([0.0015, 0.0015, 0.0016, 0.0016]);
+        assert_eq!(results, [2]);
+
+        let results = tree.intersect_aabb_f32([12.49, 12.49, 15.0, 15.0]);
+        assert_eq!(results, [3]);
+
+        assert!(tree.intersect_aabb_f32([5.0, 5.0, 6.0, 6.0]).is_empty());
+    }
+
+    #[test]
+    fn remove_works() {
+        let mut tree = build_scaled_tree();
+        tree.insert_f32(1, [-1.5, -1.5, 1.25, 1.25])
+            .expect("insert should work");
+        assert!(tree.remove_f32(1, [-1.5, -1.5, 1.25, 1.25]));
+        assert!(tree
+            .intersect_aabb_f32([-20.0, -20.0, 20.0, 20.0])
+            .is_empty());
+    }
+
+    #[test]
+    fn out_of_bounds_insert_fails() {
+        let mut tree = build_scaled_tree();
+        assert!(tree.insert_f32(1, [30.0, 30.0, 31.0, 31.0]).is_err());
+    }
+}
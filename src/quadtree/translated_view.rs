@@ -0,0 +1,55 @@
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::{QuadTree, AABB};
+
+/// A view of a [`QuadTree`] whose queries are expressed in a coordinate
+/// system that is offset from the one of the tree, e.g. screen space.
+/// Obtained from [`QuadTree::with_offset()`].
+pub struct TranslatedView<'a, ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    tree: &'a QuadTree<ElementId>,
+    dx: i32,
+    dy: i32,
+}
+
+impl<'a, ElementId> TranslatedView<'a, ElementId>
+where
+    ElementId: ElementIdType,
+{
+    #[inline]
+    pub(crate) fn new(tree: &'a QuadTree<ElementId>, dx: i32, dy: i32) -> Self {
+        Self { tree, dx, dy }
+    }
+
+    /// Converts a rectangle of the view into the coordinate system of the tree.
+    #[inline]
+    pub fn to_tree(&self, rect: &AABB) -> AABB {
+        rect.translated(self.dx, self.dy)
+    }
+
+    /// Returns the set of IDs that occupy space within the
+    /// specified bounding box given in the coordinates of the view.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    #[inline]
+    pub fn intersect_aabb(&self, rect: &AABB) -> Vec<ElementId> {
+        self.tree.intersect_aabb(&self.to_tree(rect))
+    }
+
+    /// Calls a function for each ID that occupies space within the
+    /// specified bounding box given in the coordinates of the view.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    /// * [`candidate_fn`] - The function called for each candidate element's ID.
+    #[inline]
+    pub fn intersect_aabb_fn<F>(&self, rect: &AABB, candidate_fn: F)
+    where
+        F: FnMut(ElementId),
+    {
+        self.tree
+            .intersect_aabb_fn(&self.to_tree(rect), candidate_fn)
+    }
+}
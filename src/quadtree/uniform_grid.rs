@@ -0,0 +1,180 @@
+//! Provides a flat, array-backed grid built from a uniformly subdivided `QuadTree`.
+use crate::intersections::IntersectsWith;
+use crate::quadtree::centered_aabb::CenteredAABB;
+use crate::quadtree::quadtree_element::ElementIdType;
+use crate::quadtree::AABB;
+use std::ops::RangeInclusive;
+
+/// A uniform grid of cells storing the elements of a [`QuadTree`](crate::QuadTree)
+/// whose leaves are all at the same depth, e.g. after [`pre_split_to_depth()`].
+/// Obtained from [`QuadTree::as_uniform_grid()`](crate::QuadTree::as_uniform_grid).
+///
+/// # Remarks
+/// The cells covered by a query are found by index arithmetic rather than by
+/// descending the tree, which makes the grid a fast broad phase for trees of
+/// small depths. Elements are referenced by every cell they overlap, and each
+/// element is reported at most once per query.
+///
+/// The grid is a snapshot of the tree; it does not reflect later modifications.
+///
+/// [`pre_split_to_depth()`]: crate::QuadTree::pre_split_to_depth
+#[derive(Debug, Clone)]
+pub struct UniformGrid<ElementId = u32>
+where
+    ElementId: ElementIdType,
+{
+    /// The left coordinate of the first column.
+    left: i32,
+    /// The top coordinate of the first row.
+    top: i32,
+    /// The width of each cell.
+    cell_width: i32,
+    /// The height of each cell.
+    cell_height: i32,
+    /// The number of cells along each axis.
+    cells_per_side: u32,
+    /// For each cell in row-major order, the index of its first entry in
+    /// `cell_elements`, followed by the total number of entries.
+    cell_starts: Vec<u32>,
+    /// The indices into `elements` of the elements referenced by each cell.
+    cell_elements: Vec<u32>,
+    /// The elements, along with the first column and row they overlap.
+    elements: Vec<(ElementId, AABB, u32, u32)>,
+}
+
+impl<ElementId> UniformGrid<ElementId>
+where
+    ElementId: ElementIdType,
+{
+    /// Builds a grid from the extents of the tree and its elements.
+    ///
+    /// # Arguments
+    /// * [`root`] - The extents of the tree.
+    /// * [`depth`] - The depth of the leaves of the tree.
+    /// * [`elements`] - The IDs and bounding boxes of the elements.
+    pub(crate) fn new<I>(root: CenteredAABB, depth: u8, elements: I) -> Self
+    where
+        I: IntoIterator<Item = (ElementId, AABB)>,
+    {
+        let cells_per_side = 1u32 << depth;
+        let mut grid = Self {
+            left: root.left(),
+            top: root.top(),
+            cell_width: ((root.half_width >> depth) << 1).max(1),
+            cell_height: ((root.half_height >> depth) << 1).max(1),
+            cells_per_side,
+            cell_starts: vec![0; (cells_per_side * cells_per_side) as usize + 1],
+            cell_elements: Vec::new(),
+            elements: Vec::new(),
+        };
+
+        // Count the references of each cell, then place them using the prefix sums.
+        let mut ranges = Vec::new();
+        for (id, rect) in elements {
+            let (columns, rows) = grid.cell_ranges(&rect);
+            for row in rows.clone() {
+                for column in columns.clone() {
+                    let cell = grid.cell_index(column, row);
+                    grid.cell_starts[cell] += 1;
+                }
+            }
+            grid.elements
+                .push((id, rect, *columns.start(), *rows.start()));
+            ranges.push((columns, rows));
+        }
+
+        let mut total = 0;
+        for start in grid.cell_starts.iter_mut() {
+            let count = *start;
+            *start = total;
+            total += count;
+        }
+
+        let mut next = grid.cell_starts.clone();
+        grid.cell_elements = vec![0; total as usize];
+        for (element_idx, (columns, rows)) in ranges.into_iter().enumerate() {
+            for row in rows {
+                for column in columns.clone() {
+                    let slot = &mut next[grid.cell_index(column, row)];
+                    grid.cell_elements[*slot as usize] = element_idx as u32;
+                    *slot += 1;
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Returns the number of elements stored in the grid.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether the grid stores no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the number of cells along each axis.
+    #[inline]
+    pub fn cells_per_side(&self) -> u32 {
+        self.cells_per_side
+    }
+
+    /// Returns the IDs of all elements intersecting the specified bounding box,
+    /// in no particular order.
+    ///
+    /// # Arguments
+    /// * [`rect`] - The rectangle to test for.
+    pub fn intersect_aabb(&self, rect: &AABB) -> Vec<ElementId> {
+        let mut matches = Vec::new();
+        let (columns, rows) = self.cell_ranges(rect);
+        for row in rows.clone() {
+            for column in columns.clone() {
+                let cell = self.cell_index(column, row);
+                let references = self.cell_starts[cell]..self.cell_starts[cell + 1];
+                for &element_idx in
+                    &self.cell_elements[references.start as usize..references.end as usize]
+                {
+                    let (id, elem_rect, first_column, first_row) =
+                        &self.elements[element_idx as usize];
+
+                    // Only report the element in the first cell it shares with
+                    // the query, such that it is reported exactly once.
+                    let is_first_column = column == (*first_column).max(*columns.start());
+                    let is_first_row = row == (*first_row).max(*rows.start());
+                    if is_first_column && is_first_row && rect.intersects_with(elem_rect) {
+                        matches.push(id.clone());
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    /// Determines the columns and rows of the cells overlapped by the specified box.
+    /// Coordinates outside of the grid are assigned to its border cells.
+    fn cell_ranges(&self, rect: &AABB) -> (RangeInclusive<u32>, RangeInclusive<u32>) {
+        let column =
+            |x: i32| Self::cell_coordinate(x, self.left, self.cell_width, self.cells_per_side);
+        let row =
+            |y: i32| Self::cell_coordinate(y, self.top, self.cell_height, self.cells_per_side);
+        (
+            column(rect.tl.x)..=column(rect.br.x),
+            row(rect.tl.y)..=row(rect.br.y),
+        )
+    }
+
+    #[inline]
+    fn cell_coordinate(value: i32, origin: i32, cell_size: i32, cells_per_side: u32) -> u32 {
+        let offset = (value as i64 - origin as i64).div_euclid(cell_size as i64);
+        offset.clamp(0, cells_per_side as i64 - 1) as u32
+    }
+
+    #[inline]
+    fn cell_index(&self, column: u32, row: u32) -> usize {
+        (row * self.cells_per_side + column) as usize
+    }
+}